@@ -0,0 +1,54 @@
+//! Repeated smoothing fits of short spectra, with and without a reusable work space.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use splinify::{CubicSplineFit, FitWorkspace, ParameterSplineCurveFit};
+
+// LED spectrum, from 380 to 780nm in 1nm steps, with a small ripple as measurement noise
+fn spectrum() -> (Vec<f64>, Vec<f64>) {
+    let x: Vec<f64> = (380..=780).map(f64::from).collect();
+    let y = x.iter().map(|x| (-((x - 450.0) / 12.0).powi(2)).exp() + 0.6 * (-((x - 560.0) / 50.0).powi(2)).exp()
+        + 0.002 * (x * 0.7).sin()).collect();
+    (x, y)
+}
+
+fn curfit(c: &mut Criterion) {
+    let (x, y) = spectrum();
+    let mut group = c.benchmark_group("curfit");
+    group.bench_function("new", |b| {
+        b.iter(|| CubicSplineFit::new(x.clone(), y.clone()).unwrap().smoothing_spline(black_box(0.005)).unwrap())
+    });
+    let workspace = FitWorkspace::new();
+    group.bench_function("with_workspace", |b| {
+        b.iter(|| {
+            CubicSplineFit::with_workspace(x.clone(), y.clone(), &workspace)
+                .unwrap()
+                .smoothing_spline(black_box(0.005))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn concur(c: &mut Criterion) {
+    let (u, y) = spectrum();
+    let xy: Vec<f64> = u.iter().zip(&y).flat_map(|(&u, &y)| [u, y]).collect();
+    let mut group = c.benchmark_group("concur");
+    group.bench_function("new", |b| {
+        b.iter(|| {
+            ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone()).unwrap().smoothing_spline(black_box(0.005)).unwrap()
+        })
+    });
+    let workspace = FitWorkspace::new();
+    group.bench_function("with_workspace", |b| {
+        b.iter(|| {
+            ParameterSplineCurveFit::<3, 2>::with_workspace(u.clone(), xy.clone(), &workspace)
+                .unwrap()
+                .smoothing_spline(black_box(0.005))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, curfit, concur);
+criterion_main!(benches);
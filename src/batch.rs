@@ -0,0 +1,54 @@
+//! Parallel Batch Fits
+//!
+//! Fits many data sets in parallel, using [rayon](https://docs.rs/rayon), with the `rayon` feature enabled.
+//! Each worker thread uses its own [`FitWorkspace`], so the work arrays are allocated once per thread, and not for
+//! each data set. The results are returned in the order of the data sets, with an error for each data set which
+//! could not be fitted, without affecting the other fits.
+//!
+//! The Dierckx routines `curfit`, `concur` and `clocur`, and the routines called by them, can be called
+//! concurrently: they have no `common` blocks, and no `save` or `data` statements, and their local arrays are
+//! small, fixed size arrays, which gfortran allocates on the stack. All input, output and work arrays are passed
+//! as arguments, and each fitter owns its own arrays.
+
+use crate::{FitResult, FitWorkspace, SplineCurveFit};
+use rayon::prelude::*;
+use spliny::SplineCurve;
+
+/// A data set for a batch fit: x values, y values, and optional weights.
+pub type Dataset = (Vec<f64>, Vec<f64>, Option<Vec<f64>>);
+
+impl<const K: usize> SplineCurveFit<K> {
+    /**
+     * Fits a collection of data sets in parallel, with the fit method `fit`, and returns the results in the order of
+     * the data sets.
+     *
+     * For example, `SplineCurveFit::<3>::batch(datasets, |fit| fit.smoothing_spline(0.01))` returns the smoothing
+     * splines, and their fit reports.
+     */
+    pub fn batch<T: Send>(
+        datasets: Vec<Dataset>,
+        fit: impl Fn(Self) -> FitResult<T> + Sync + Send,
+    ) -> Vec<FitResult<T>> {
+        datasets
+            .into_par_iter()
+            .map_init(FitWorkspace::new, |workspace, (x, y, w)| {
+                let fitter = Self::with_workspace(x, y, workspace)?;
+                match w {
+                    Some(w) => fit(fitter.set_weights(w)?),
+                    None => fit(fitter),
+                }
+            })
+            .collect()
+    }
+
+    /**
+     * Smoothing splines for a collection of data sets, fitted in parallel, in the order of the data sets.
+     *
+     * Arguments:
+     * - datasets: x values, y values, and optional weights, for each of the fits
+     * - rms: root mean square error
+     */
+    pub fn smoothing_spline_batch(datasets: Vec<Dataset>, rms: f64) -> Vec<FitResult<SplineCurve<K, 1>>> {
+        Self::batch(datasets, |fit| fit.smoothing_spline(rms).map(SplineCurve::from))
+    }
+}
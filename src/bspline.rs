@@ -0,0 +1,87 @@
+//! B-spline basis functions, for the fitting and evaluation methods implemented in Rust.
+//!
+//! Knots are given as the full knot vector `t`, with length `n`, of a spline with degree `k`,
+//! which has `n-k-1` coefficients, and is defined on the interval `t[k]..=t[n-k-1]`.
+
+/// Maximum supported spline degree plus one.
+pub(crate) const MAX_ORDER: usize = 6;
+
+/// Index `l` of the knot interval `t[l] <= x < t[l+1]` containing `x`, limited to the range
+/// `k..=n-k-2`, such that values outside the spline's domain map to its first or last interval.
+pub(crate) fn interval(t: &[f64], k: usize, x: f64) -> usize {
+    let n = t.len();
+    let l = t.partition_point(|&ti| ti <= x);
+    l.saturating_sub(1).clamp(k, n - k - 2)
+}
+
+/// Values of the `k+1` non-zero B-splines `B[l-k..=l]` at `x`, with `l` the knot interval
+/// containing `x`, using the recurrence relation of de Boor and Cox, as in Dierckx' `fpbspl`.
+pub(crate) fn basis(t: &[f64], k: usize, x: f64, l: usize) -> [f64; MAX_ORDER] {
+    let mut h = [0.0; MAX_ORDER];
+    let mut hh = [0.0; MAX_ORDER];
+    h[0] = 1.0;
+    for j in 1..=k {
+        hh[..j].copy_from_slice(&h[..j]);
+        h[0] = 0.0;
+        for i in 1..=j {
+            let li = l + i;
+            let lj = li - j;
+            let f = if t[li] > t[lj] { hh[i - 1] / (t[li] - t[lj]) } else { 0.0 };
+            h[i - 1] += f * (t[li] - x);
+            h[i] = f * (x - t[lj]);
+        }
+    }
+    h
+}
+
+/// Values of the derivatives of order `nu` of the `k+1` non-zero B-splines `B[l-k..=l]` at `x`.
+///
+/// The basis functions of degree `k-nu` are raised to degree `k` with the derivative recurrence
+/// `D B(i,d) = d * (B(i,d-1)/(t[i+d]-t[i]) - B(i+1,d-1)/(t[i+d+1]-t[i+1]))`.
+pub(crate) fn basis_derivatives(t: &[f64], k: usize, x: f64, l: usize, nu: usize) -> [f64; MAX_ORDER] {
+    if nu > k {
+        return [0.0; MAX_ORDER];
+    }
+    let mut b = basis(t, k - nu, x, l);
+    for d in (k - nu + 1)..=k {
+        let mut db = [0.0; MAX_ORDER];
+        for (j, dbj) in db.iter_mut().enumerate().take(d + 1) {
+            let i = l + j - d;
+            let left = if j >= 1 && t[i + d] > t[i] { b[j - 1] / (t[i + d] - t[i]) } else { 0.0 };
+            let right = if j < d && t[i + d + 1] > t[i + 1] { b[j] / (t[i + d + 1] - t[i + 1]) } else { 0.0 };
+            *dbj = d as f64 * (left - right);
+        }
+        b = db;
+    }
+    b
+}
+
+/// Jumps of the `k`-th derivative of the B-splines at the interior knots `t[k+1..n-k-1]`, as in Dierckx' `fpdisc`.
+///
+/// Returns, for each interior knot `t[l]`, the index of the first B-spline involved, `l-k-1`, and the jumps of
+/// the `k+2` B-splines `B[l-k-1..=l]`, scaled by the average knot interval raised to the power `k`.
+pub(crate) fn discontinuity_jumps(t: &[f64], k: usize) -> Vec<(usize, [f64; MAX_ORDER + 1])> {
+    let n = t.len();
+    (k + 1..n - k - 1).map(|l| (l - k - 1, jump(t, k, l - 1, l))).collect()
+}
+
+/// Jumps of the `k`-th derivative of a periodic spline at the begin of its period, `t[k]`, scaled as for
+/// [`discontinuity_jumps`], for the `k+2` B-splines `B[n-2k-2..=n-k-2]`, which are the same as `B[-1..=k]`.
+pub(crate) fn periodic_discontinuity_jump(t: &[f64], k: usize) -> [f64; MAX_ORDER + 1] {
+    jump(t, k, t.len() - k - 2, k)
+}
+
+// jump of the k-th derivative between knot intervals `left` and `right`
+fn jump(t: &[f64], k: usize, left: usize, right: usize) -> [f64; MAX_ORDER + 1] {
+    let n = t.len();
+    let nint = n - 2 * k - 1;
+    let fac = ((t[n - k - 1] - t[k]) / nint as f64).powi(k as i32);
+    let dl = basis_derivatives(t, k, 0.5 * (t[left] + t[left + 1]), left, k);
+    let dr = basis_derivatives(t, k, 0.5 * (t[right] + t[right + 1]), right, k);
+    let mut jump = [0.0; MAX_ORDER + 1];
+    for j in 0..=k {
+        jump[j] -= fac * dl[j];
+        jump[j + 1] += fac * dr[j];
+    }
+    jump
+}
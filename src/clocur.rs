@@ -10,8 +10,10 @@
 //! To build periodic splines where the curve forms a closed loop, the first and last data points must
 //! coincide.
 
-use super::FitError;
-use crate::Result;
+use super::{validate, Criterion, FitError, FitResult, FittedCurve, SmoothingSelection};
+use std::iter::once;
+use std::mem::take;
+use crate::select::{Edf, Smoother};
 use dierckx_sys::clocur_;
 use spliny::SplineCurve;
 
@@ -21,11 +23,12 @@ pub struct ClosedParameterSplineCurveFit<const K: usize, const N: usize> {
     xn: Vec<f64>, // data (x,y,..) coordinates
     u: Vec<f64>,
     w: Vec<f64>, // weight factors
-    ipar: i32,
 
     t: Vec<f64>,
     c: Vec<f64>,
     e_rms: Option<f64>,
+    ier: i32,
+    edf: bool, // calculate the effective degrees of freedom of smoothing fits
     n: i32,
 
     // work space values
@@ -55,24 +58,41 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
     /// constructed as [x0, y0, z0, x1, y1, z1, x2 ...]. Its the number of coordinates is m, its size is
     /// N * m. The first and last data points must coincide.
 
-    pub fn new(u: Vec<f64>, xn: Vec<f64>) -> Result<Self> {
+    pub fn new(u: Vec<f64>, xn: Vec<f64>) -> FitResult<Self> {
+        Self::build(Some(u), xn)
+    }
+
+    /// Constructor, taking the curve coordinates as input, with the curve parameter values calculated as `clocur`
+    /// does for `ipar=0`: as the cumulative distance between the data points, normalized to a range from 0.0 to 1.0.
+    /// These values are available through [`u`](Self::u), and can be used to map the data points to the fitted curve.
+    ///
+    /// The coordinates in `xn` are arranged as for [`new`](Self::new); consecutive points should not coincide,
+    /// except for the first and last point.
+    pub fn with_chord_length(xn: Vec<f64>) -> FitResult<Self> {
+        Self::build(None, xn)
+    }
+
+    fn build(u: Option<Vec<f64>>, xn: Vec<f64>) -> FitResult<Self> {
         let k = K as i32;
         if ![1, 3, 5].contains(&k) {
-            return Err(FitError(208).into());
+            return Err(FitError::InvalidDegree { routine: "clocur", k: K, allowed: "1, 3 or 5" });
         };
         let idim = if (1..=10).contains(&N) {
             N as i32
         } else {
-            return Err(FitError(200).into());
+            return Err(FitError::InvalidDimension { routine: "clocur", n: N });
         };
-        let m = u.len() as i32;
+        let m = u.as_ref().map_or(xn.len() / N, Vec::len) as i32;
         if m < 2 {
-            return Err(FitError(201).into());
+            return Err(FitError::TooFewDataPoints { routine: "clocur", found: m as usize, min: 2 });
         };
         let mx = m * idim;
         if xn.len() as i32 != mx {
-            return Err(FitError::new(202).into());
+            return Err(FitError::SizeMismatch { routine: "clocur", what: "xn", expected: mx as usize, found: xn.len() });
         }
+        validate::finite("clocur", "xn", &xn)?;
+        let u = u.unwrap_or_else(|| chord_lengths::<N>(&xn));
+        validate::strictly_ascending("clocur", "u", &u)?;
 
         // Validate that first and last points coincide
         let n_dim = N;
@@ -81,13 +101,13 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
             let first = xn[d];
             let last = xn[(m_usize - 1) * n_dim + d];
             if (first - last).abs() > 1e-10 {
-                return Err(
-                    format!("clocur requires first and last points to coincide, but dimension {d} differs: {first} vs {last}").into()
-                );
+                return Err(FitError::InvalidInput {
+                    routine: "clocur",
+                    reason: format!("first and last points should coincide, but dimension {d} differs: {first} vs {last}"),
+                });
             }
         }
 
-        let ipar = 1; // user-supplied parameter values
         let w_vec = vec![1.0; m as usize];
 
         let nest = m + 2 * k;
@@ -104,7 +124,6 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
             u,
             xn,
             w: w_vec,
-            ipar,
             t: t_vec,
             c: c_vec,
             wrk: wrk_vec,
@@ -116,19 +135,30 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
             idim,
             n,
             e_rms: None,
+            ier: 0,
+            edf: false,
         })
     }
 
-    pub fn weights(mut self, weights: Vec<f64>) -> Result<Self> {
-        if weights.len() == self.u.len() {
-            self.w = weights;
-            Ok(self)
-        } else {
-            Err(FitError(203).into())
-        }
+    /// Curve parameter values, as supplied, or as calculated for [`with_chord_length`](Self::with_chord_length).
+    pub fn u(&self) -> &[f64] {
+        &self.u
+    }
+
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn weights(mut self, weights: Vec<f64>) -> FitResult<Self> {
+        validate::weights("clocur", &weights, self.u.len())?;
+        self.w = weights;
+        Ok(self)
+    }
+
+    /// Calculates the `edf` of smoothing fits, see [`select`](crate::select#effective-degrees-of-freedom).
+    pub fn with_edf(mut self) -> Self {
+        self.edf = true;
+        self
     }
 
-    fn clocur(&mut self, iopt: i32, e_rms: Option<f64>, knots: Option<Vec<f64>>) -> i32 {
+    fn clocur(&mut self, iopt: i32, e_rms: Option<f64>, knots: Option<Vec<f64>>) -> FitResult<()> {
         let mut fp = 0.0;
         let s = if let Some(e) = e_rms {
             self.m as f64 * e.powi(2)
@@ -136,6 +166,7 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
             0.0
         };
 
+        let ipar = 1;
         let nc = self.c.len() as i32;
         let lwrk = self.wrk.len() as i32;
 
@@ -147,10 +178,10 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
         unsafe {
             clocur_(
                 &iopt,
-                &self.ipar,
+                &ipar,
                 &self.idim,
                 &self.m,
-                self.u.as_ptr(),
+                self.u.as_mut_ptr(),
                 &self.mx,
                 self.xn.as_ptr(),
                 self.w.as_ptr(),
@@ -169,7 +200,64 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
             );
         }
         self.e_rms = Some((fp / self.m as f64).sqrt());
-        ierr
+        self.ier = ierr;
+        if ierr <= 0 {
+            Ok(())
+        } else {
+            Err(FitError::from_ier("clocur", ierr, self.nest, s))
+        }
+    }
+
+    // closed spline and fit report of the last fit, with `iterations` the number of fits calculated; the report
+    // treats the spline as periodic
+    fn fitted(mut self, iterations: usize, edf: Edf) -> FittedCurve<K, N> {
+        let (u, xn, w) = (take(&mut self.u), take(&mut self.xn), take(&mut self.w));
+        let ier = self.ier;
+        let spline: SplineCurve<K, N> = self.into();
+        let smoother = Smoother {
+            routine: "clocur",
+            u: &u,
+            xn: &xn,
+            w: &w,
+            fixed: (0, 0),
+            periodic: true,
+            constraints: 0,
+        };
+        let report = smoother.report(&spline, ier, iterations, edf);
+        FittedCurve { spline, report }
+    }
+
+    /**
+     * Least Squares Spline: Weighted least squares closed spline with given interior knots
+     *
+     * The interior knots should be in strictly ascending order, inside the range of u, with enough data points
+     * between them to determine all the B-spline coefficients (the Schoenberg-Whitney conditions), and at least
+     * max(1, K-1) knots.
+     * The boundary knots are added at the first and last value of u, and extended periodically.
+     */
+    pub fn least_squares_spline(mut self, knots: Vec<f64>) -> FitResult<FittedCurve<K, N>> {
+        let m = self.u.len();
+        let (ub, ue) = (self.u[0], self.u[m - 1]);
+        validate::interior_knots("clocur", &knots, ub, ue)?;
+        let nk = knots.len();
+        let min = 1.max(K - 1);
+        if nk < min || nk + 2 > m {
+            return Err(FitError::InvalidInput {
+                routine: "clocur",
+                reason: format!("{min} to {} interior knots are required, found {nk}", m.saturating_sub(2)),
+            });
+        }
+        let per = ue - ub;
+        let inner: Vec<f64> = once(ub).chain(knots).chain(once(ue)).collect();
+        let t: Vec<f64> = (1..=K)
+            .rev()
+            .map(|j| inner[nk + 1 - j] - per)
+            .chain(inner.iter().copied())
+            .chain((1..=K).map(|j| inner[j] + per))
+            .collect();
+        validate::periodic_schoenberg_whitney("clocur", &t, K, &self.u)?;
+        self.clocur(-1, Some(0.0), Some(t))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
     }
 
     /**
@@ -177,35 +265,28 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
 
 
     */
-    pub fn interpolating_spline(mut self) -> Result<SplineCurve<K, N>> {
-        let ierr = self.clocur(0, Some(0.0), None);
-        if ierr <= 0 {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
-        }
+    pub fn interpolating_spline(mut self) -> FitResult<FittedCurve<K, N>> {
+        self.clocur(0, Some(0.0), None)?;
+        Ok(self.fitted(1, Edf::LeastSquares))
     }
 
     /**
      * Fit a smoothing spline
      *
-     * nrguments:
+     * Arguments:
      * - rms: root mean square error
      *
      */
-    pub fn smoothing_spline(mut self, rms: f64) -> Result<SplineCurve<K, N>> {
-        let ierr = self.clocur(0, Some(rms), None);
-        if ierr > 0 {
-            Err(FitError(ierr).into())
-        } else {
-            Ok(self.into())
-        }
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<FittedCurve<K, N>> {
+        self.clocur(0, Some(rms), None)?;
+        let edf = Edf::Smoothing(self.edf);
+        Ok(self.fitted(1, edf))
     }
 
     /**
      * Fit a best-fit smoothing spline by decreasing rms target
      *
-     * nrguments:
+     * Arguments:
      * - rms_start: root mean square error start value
      * - rms_scale_ratio: `rms *= rms_scale_ratio` for each step
      * - converged: boolean convergence function, with arguments
@@ -223,36 +304,72 @@ impl<const K: usize, const N: usize> ClosedParameterSplineCurveFit<K, N> {
         rms_scale_ratio: f64,
         converged: impl Fn(i32, i32, f64, f64) -> bool,
         n_iter: Option<usize>,
-    ) -> Result<SplineCurve<K, N>> {
+    ) -> FitResult<FittedCurve<K, N>> {
         let n_iter = n_iter.unwrap_or(40);
-        let ierr = self.clocur(0, Some(rms_start), None);
-        if ierr > 0 {
-            return Err(FitError(ierr).into());
-        }
+        self.clocur(0, Some(rms_start), None)?;
         let mut rms = self.e_rms.unwrap();
         let mut n_prev;
         let mut rms_prev;
-        for _ in 0..n_iter {
+        for i in 0..n_iter {
             n_prev = self.n;
             rms_prev = rms;
-            let ierr = self.clocur(1, Some(rms * rms_scale_ratio), None);
+            self.clocur(1, Some(rms * rms_scale_ratio), None)?;
             rms = self.e_rms.unwrap();
-            if ierr > 0 {
-                return Err(FitError(ierr).into());
-            }
             if converged(self.n, self.n - n_prev, rms, rms_prev - rms) {
-                let ierr = self.clocur(0, Some(rms_prev), None);
-                if ierr > 0 {
-                    return Err(FitError(ierr).into());
-                } else {
-                    return Ok(self.into());
-                }
+                self.clocur(0, Some(rms_prev), None)?;
+                let edf = Edf::Smoothing(self.edf);
+                return Ok(self.fitted(i + 3, edf));
             };
         }
-        Err(FitError(206).into())
+        Err(FitError::NotConverged { routine: "clocur", iterations: n_iter })
+    }
+
+    /**
+     * Closed curve, with the target rms value selected from a set of candidate values
+     *
+     * Fits a closed `clocur` smoothing curve for each of the target rms values. The candidates are scored as periodic
+     * splines: only the coefficients of one period count in the effective degrees of freedom, and the roughness
+     * includes the jump at the closing point; see the [`select`](crate::select) module.
+     *
+     * Arguments:
+     * - rms: target root mean square error values, for example from [`rms_range`](crate::rms_range)
+     * - criterion: selection criterion
+     *
+     */
+    pub fn select_smoothing(&self, rms: &[f64], criterion: Criterion) -> FitResult<SmoothingSelection<K, N>> {
+        let smoother = Smoother {
+            routine: "clocur",
+            u: &self.u,
+            xn: &self.xn,
+            w: &self.w,
+            fixed: (0, 0),
+            periodic: true,
+            constraints: 0,
+        };
+        smoother.select(rms, criterion, |rms| {
+            let mut fit = self.clone();
+            fit.clocur(0, Some(rms), None)?;
+            Ok(fit.into())
+        })
     }
 } // impl ClosedParametricCurveSplineFit
 
+// cumulative distance between the data points, normalized to a range from 0.0 to 1.0, as calculated by `clocur` and
+// `parcur` for `ipar=0`
+pub(crate) fn chord_lengths<const N: usize>(xn: &[f64]) -> Vec<f64> {
+    let mut u: Vec<f64> = once(0.0)
+        .chain(xn.chunks(N).zip(xn.chunks(N).skip(1)).scan(0.0, |s, (a, b)| {
+            *s += a.iter().zip(b).map(|(a, b)| (b - a).powi(2)).sum::<f64>().sqrt();
+            Some(*s)
+        }))
+        .collect();
+    let m = u.len();
+    let ue = u[m - 1];
+    u.iter_mut().for_each(|u| *u /= ue);
+    u[m - 1] = 1.0;
+    u
+}
+
 impl<const K: usize, const N: usize> From<ClosedParameterSplineCurveFit<K, N>>
     for SplineCurve<K, N>
 {
@@ -0,0 +1,245 @@
+//! Convexity Constrained Cubic Spline-Fit for Single Output Data
+//!
+//! Rust wrapper of Dierckx' `concon` Fortran subroutine:
+//!
+//! ```fortran
+//!  subroutine concon(iopt,m,x,y,w,v,s,nest,maxtr,maxbin,n,t,c,sq,sx,bind,wrk,lwrk,iwrk,kwrk,ier)
+//! ```
+//! Wrapper `ConvexSplineCurveFit` fits a cubic spline s(x) to a set of data points (x<sub>i</sub>,y<sub>i</sub>),
+//! subject to local convexity constraints `s''(x(i))*v(i) <= 0`, with `v(i)` set to 1 for points where the spline
+//! should be concave, -1 where it should be convex, and 0 for points without a constraint.
+
+use crate::dierckx::concon_;
+use super::{validate, FitError, FitResult, FittedCurve};
+use crate::select::{Edf, Smoother};
+use spliny::SplineCurve;
+
+// initial sizes of the tree structure and the number of knots with a zero second derivative, as recommended by Dierckx
+const MAXTR: i32 = 100;
+const MAXBIN: i32 = 10;
+
+#[derive(Clone)]
+pub struct ConvexSplineCurveFit {
+    // input values
+    x: Vec<f64>, // data x coordinates
+    y: Vec<f64>, // data y coordinates
+    w: Vec<f64>, // weight factors
+    v: Vec<f64>, // convexity constraints
+
+    t: Vec<f64>,
+    c: Vec<f64>,
+    e_rms: Option<f64>,
+    n: i32,
+
+    // work space values
+    sx: Vec<f64>,    // used for successive tries
+    bind: Vec<i32>,  // Fortran logical array, used for successive tries
+    wrk: Vec<f64>,
+    iwrk: Vec<i32>,
+    m: i32,
+    nest: i32,
+    maxtr: i32,
+    maxbin: i32,
+    started: bool,  // a call with iopt=1 must be preceded by a successful call
+}
+
+/**
+
+Fit cubic B-Spline function with convexity constraints to a set of data points
+
+Wrapper for Dierckx' `concon` subroutine.
+
+*/
+impl ConvexSplineCurveFit {
+    /// Constructor, with inputs x, y, and the convexity constraints v, all with equal length.
+    ///
+    /// The values x should be in strictly ascending order, and at least 4 points are required.
+    /// A value v(i) of 1.0 requires the spline to be concave at x(i), a value -1.0 requires it to be convex,
+    /// and 0.0 leaves the spline unconstrained at this point.
+    pub fn new(x: Vec<f64>, y: Vec<f64>, v: Vec<f64>) -> FitResult<Self> {
+        let m = x.len() as i32;
+        if m < 4 {
+            return Err(FitError::TooFewDataPoints { routine: "concon", found: m as usize, min: 4 });
+        };
+        if y.len() != x.len() {
+            return Err(FitError::SizeMismatch { routine: "concon", what: "y", expected: x.len(), found: y.len() });
+        }
+        if v.len() != x.len() {
+            return Err(FitError::SizeMismatch { routine: "concon", what: "v", expected: x.len(), found: v.len() });
+        }
+        validate::strictly_ascending("concon", "x", &x)?;
+        validate::finite("concon", "y", &y)?;
+        validate::finite("concon", "v", &v)?;
+        let w_vec = vec![1.0; m as usize];
+
+        let nest = m + 4;
+        let t_vec = vec![0.0; nest as usize];
+        let c_vec = vec![0.0; nest as usize];
+        let sx_vec = vec![0.0; m as usize];
+        let bind_vec = vec![0i32; nest as usize];
+
+        let mut fit = Self {
+            x,
+            y,
+            w: w_vec,
+            v,
+            t: t_vec,
+            c: c_vec,
+            sx: sx_vec,
+            bind: bind_vec,
+            wrk: Vec::new(),
+            iwrk: Vec::new(),
+            m,
+            nest,
+            maxtr: MAXTR,
+            maxbin: MAXBIN.min(nest - 6),
+            n: 0,
+            e_rms: None,
+            started: false,
+        };
+        fit.allocate_workspace();
+        Ok(fit)
+    }
+
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn set_weights(mut self, weights: Vec<f64>) -> FitResult<Self> {
+        validate::weights("concon", &weights, self.x.len())?;
+        self.w = weights;
+        Ok(self)
+    }
+
+    fn allocate_workspace(&mut self) {
+        let lwrk = self.m * 4 + self.nest * 8 + self.maxbin * (self.maxbin + self.nest + 1);
+        let kwrk = self.maxtr * 4 + 2 * (self.maxbin + 1);
+        self.wrk = vec![0f64; lwrk as usize];
+        self.iwrk = vec![0i32; kwrk as usize];
+    }
+
+    /// Calls `concon`, and restarts with a larger work space if the tree structure (`ier=2`), or the number of knots
+    /// with a zero second derivative (`ier=1`), exceed the available storage space.
+    /// Returns the error flag, and the number of calls.
+    fn concon(&mut self, iopt: i32, e_rms: f64) -> (i32, usize) {
+        let s = self.m as f64 * e_rms.powi(2);
+        let mut iopt = iopt;
+        let mut calls = 0;
+        loop {
+            let ierr = self.concon_call(iopt, s);
+            calls += 1;
+            match ierr {
+                1 if self.maxbin < self.nest - 6 => self.maxbin = (2 * self.maxbin).min(self.nest - 6),
+                2 if self.maxtr < MAXTR << 10 => self.maxtr *= 4,
+                _ => return (ierr, calls),
+            }
+            self.allocate_workspace();
+            iopt = 0;
+        }
+    }
+
+    fn concon_call(&mut self, iopt: i32, s: f64) -> i32 {
+        let mut sq = 0.0;
+        let lwrk = self.wrk.len() as i32;
+        let kwrk = self.iwrk.len() as i32;
+        let mut ierr = 0;
+        unsafe {
+            concon_(
+                &iopt,
+                &self.m,
+                self.x.as_ptr(),
+                self.y.as_ptr(),
+                self.w.as_ptr(),
+                self.v.as_ptr(),
+                &s,
+                &self.nest,
+                &self.maxtr,
+                &self.maxbin,
+                &mut self.n,
+                self.t.as_mut_ptr(),
+                self.c.as_mut_ptr(),
+                &mut sq,
+                self.sx.as_mut_ptr(),
+                self.bind.as_mut_ptr(),
+                self.wrk.as_mut_ptr(),
+                &lwrk,
+                self.iwrk.as_mut_ptr(),
+                &kwrk,
+                &mut ierr,
+            );
+        }
+        self.e_rms = Some((sq / self.m as f64).sqrt());
+        self.started = ierr <= 0;
+        ierr
+    }
+
+    fn spline(&self) -> SplineCurve<3, 1> {
+        let n = self.n as usize;
+        SplineCurve::new(self.t[..n].to_vec(), self.c[..n - 4].to_vec())
+    }
+
+    fn result(&self, (ierr, calls): (i32, usize), rms: f64) -> FitResult<FittedCurve<3, 1>> {
+        match ierr {
+            i32::MIN..=0 => {
+                let spline = self.spline();
+                let smoother = Smoother {
+                    routine: "concon",
+                    u: &self.x,
+                    xn: &self.y,
+                    w: &self.w,
+                    fixed: (0, 0),
+                    periodic: false,
+                    constraints: 0,
+                };
+                let report = smoother.report(&spline, ierr, calls, Edf::None);
+                Ok(FittedCurve { spline, report })
+            }
+            1 => Err(FitError::InsufficientStorage { routine: "concon", what: "maxbin", size: self.maxbin as usize }),
+            2 => Err(FitError::InsufficientStorage { routine: "concon", what: "maxtr", size: self.maxtr as usize }),
+            3 => Err(FitError::ConstraintsNotSatisfied {
+                routine: "concon",
+                reason: "no solution found for the quadratic programming problem, probably caused by rounding errors",
+            }),
+            4 => Err(FitError::InsufficientStorage { routine: "concon", what: "nest", size: self.nest as usize }),
+            5 => Err(FitError::ConstraintsNotSatisfied {
+                routine: "concon",
+                reason: "the minimum number of knots exceeds m+4, as convexity and concavity constraints alternate \
+                    too strongly; add interpolated data points with a small weight and no constraint",
+            }),
+            _ => Err(FitError::from_ier("concon", ierr, self.nest, self.m as f64 * rms.powi(2))),
+        }
+    }
+
+    /**
+     * Smoothing Spline
+     *
+     * A cubic spline with a minimal number of knots, satisfying the convexity constraints, with error less than the
+     * specified rms value. If adding more knots does not reduce the error any further, the best spline found is
+     * returned, which satisfies the constraints, but has an error larger than the rms value.
+     */
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<FittedCurve<3, 1>> {
+        let status = self.concon(0, rms);
+        self.result(status, rms)
+    }
+
+    /**
+     * Smoothing Spline, continued from the last fit
+     *
+     * Non-consuming version of [`smoothing_spline`](Self::smoothing_spline), which continues with the knots found in
+     * the previous call (`iopt=1`), if that call was successful. Use this to obtain a sequence of fits with
+     * decreasing rms values efficiently.
+     */
+    pub fn smoothing_spline_continued(&mut self, rms: f64) -> FitResult<FittedCurve<3, 1>> {
+        let iopt = if self.started { 1 } else { 0 };
+        let status = self.concon(iopt, rms);
+        self.result(status, rms)
+    }
+
+    /// Root mean square error of the last fit.
+    pub fn e_rms(&self) -> Option<f64> {
+        self.e_rms
+    }
+} // impl ConvexSplineCurveFit
+
+impl From<ConvexSplineCurveFit> for SplineCurve<3, 1> {
+    fn from(sp: ConvexSplineCurveFit) -> Self {
+        sp.spline()
+    }
+}
@@ -12,15 +12,19 @@
 
 
 
-use std::iter::repeat;
+use std::iter::repeat_n;
 //use crate::dierckx::{concur_};
 use dierckx_sys::{concur_};
-use super::{FitError};
-use crate::Result;
+use super::{validate, Criterion, FitError, FitResult, FittedCurve, SmoothingSelection};
+use std::mem::take;
+use crate::curfit::cardinal_knots;
+use crate::interior::{self, InteriorConstraint};
+use crate::robust::{reweighted_fit, RobustFit, RobustLoss};
+use crate::select::{Edf, Smoother};
+use crate::workspace::{sized, Buffers, FitWorkspace};
 use spliny::SplineCurve;
 
 
-#[derive(Clone)]
 pub struct ParameterSplineCurveFit<const K:usize, const N:usize> {
     // input values
     xn: Vec<f64>, // data (x,y,..) coordinates
@@ -28,10 +32,13 @@ pub struct ParameterSplineCurveFit<const K:usize, const N:usize> {
     w: Vec<f64>,    // weight factors, 
     xb: Vec<f64>,
     xe: Vec<f64>,
+    constraints: Vec<InteriorConstraint<N>>,
 
     t: Vec<f64>,
     c: Vec<f64>,
     e_rms: Option<f64>,
+    ier: i32,
+    edf: bool, // calculate the effective degrees of freedom of smoothing fits
     n: i32,
 
     // work space values
@@ -46,6 +53,7 @@ pub struct ParameterSplineCurveFit<const K:usize, const N:usize> {
     nest: i32,
     k: i32,
     idim: i32,
+    workspace: Option<FitWorkspace>, // to return the arrays to after the fit
 }
 
 
@@ -71,18 +79,31 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
     pub fn new(
         u: Vec<f64>,
         xn: Vec<f64>,
-    ) -> Result<Self> {
+    ) -> FitResult<Self> {
+        Self::build(u, xn, None)
+    }
+
+    /// Constructor, as `new`, using the arrays kept in a work space, and returning them to it after the fit.
+    ///
+    /// Use this to fit many data sets in sequence, without allocating the work arrays for each of them; see the
+    /// [`workspace`](crate::workspace) module.
+    pub fn with_workspace(u: Vec<f64>, xn: Vec<f64>, workspace: &FitWorkspace) -> FitResult<Self> {
+        Self::build(u, xn, Some(workspace.clone()))
+    }
+
+    fn build(u: Vec<f64>, xn: Vec<f64>, workspace: Option<FitWorkspace>) -> FitResult<Self> {
 
         let k = K as i32;
-        if ![1,3,5].contains(&(k as i32)) { return Err(FitError(208).into()) };
+        if ![1,3,5].contains(&k) { return Err(FitError::InvalidDegree { routine: "concur", k: K, allowed: "1, 3 or 5" }) };
         let idim =  if (1..=10).contains(&N) { N as i32 } else {
-                return Err(FitError(200).into())
+                return Err(FitError::InvalidDimension { routine: "concur", n: N })
             };
         let m = u.len() as i32; // number of coordinates
-        if m<2 {return Err(FitError(201).into())};
+        if m<2 {return Err(FitError::TooFewDataPoints { routine: "concur", found: m as usize, min: 2 })};
         let mx = m * idim;
-        if xn.len() as i32!= mx { return Err(FitError::new(202).into())}
-        let w_vec = vec![1.0;m as usize];
+        if xn.len() as i32!= mx { return Err(FitError::SizeMismatch { routine: "concur", what: "xn", expected: mx as usize, found: xn.len() })}
+        validate::strictly_ascending("concur", "u", &u)?;
+        validate::finite("concur", "xn", &xn)?;
 
         let xb = Vec::new();
         let ib = 0;
@@ -91,18 +112,21 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
         let xe = Vec::new();
         let ie = 0;
 
+        let buffers = workspace.as_ref().map(FitWorkspace::take).unwrap_or_default();
+        let mut w_vec = sized(buffers.w, m as usize);
+        w_vec.fill(1.0);
         let nest = m+k+1 + 2*(k-1); 
         let n = nest;  // length of tc
-        let t_vec = vec![0.0; nest as usize];
-        let c_vec = vec![0.0; (nest * idim) as usize];
+        let t_vec = sized(buffers.t, nest as usize);
+        let c_vec = sized(buffers.c, (nest * idim) as usize);
 
-        let iwrk_vec = vec![0i32; nest as usize];
+        let iwrk_vec = sized(buffers.iwrk, nest as usize);
 
-        let wrk_vec = vec![0f64; (m*(k+1)+nest*(6+idim+3*k)) as usize];
-        let xx_vec = vec![0.0; (idim*m) as usize];
-        let cp_vec = vec![0.0; (2 * (k+1) * idim) as usize];
+        let wrk_vec = sized(buffers.wrk, (m*(k+1)+nest*(6+idim+3*k)) as usize);
+        let xx_vec = sized(buffers.xx, (idim*m) as usize);
+        let cp_vec = sized(buffers.cp, (2 * (k+1) * idim) as usize);
 
-        Ok(Self { u, xn, w: w_vec, xb, xe, t: t_vec, c: c_vec, wrk: wrk_vec, iwrk: iwrk_vec, xx: xx_vec, cp: cp_vec, ib, ie, m, mx, nest, k, idim, n, e_rms: None})
+        Ok(Self { u, xn, w: w_vec, xb, xe, constraints: Vec::new(), t: t_vec, c: c_vec, wrk: wrk_vec, iwrk: iwrk_vec, xx: xx_vec, cp: cp_vec, ib, ie, m, mx, nest, k, idim, n, e_rms: None, ier: 0, edf: false, workspace})
 
     }
 
@@ -118,36 +142,55 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
         v
     }
 
-    pub fn weights(mut self, weights: Vec<f64>) -> Result<Self> {
-        if weights.len() == self.u.len() {
-            self.w = weights;
-            Ok(self)
-        } else {
-            Err(FitError(203).into())
-        }
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn weights(mut self, weights: Vec<f64>) -> FitResult<Self> {
+        validate::weights("concur", &weights, self.u.len())?;
+        self.w = weights;
+        Ok(self)
     }
 
-    pub fn begin_constraints<const D: usize>(mut self, ub: [[f64;N];D]) -> Result<Self> {
+    pub fn begin_constraints<const D: usize>(mut self, ub: [[f64;N];D]) -> FitResult<Self> {
         if D<=(K+1)/2+1 {
             self.xb = ub.iter().flatten().cloned().collect();
             self.ib = D as i32 -1;
             Ok(self)
         } else {
-            Err(FitError(204).into())
+            Err(FitError::TooManyConstraints { routine: "concur", max: (K+1)/2+1, found: D })
         }
     }
 
-    pub fn end_constraints<const D: usize>(mut self, ub: [[f64;N];D]) -> Result<Self> {
+    pub fn end_constraints<const D: usize>(mut self, ub: [[f64;N];D]) -> FitResult<Self> {
         if D<=(K+1)/2+1 {
             self.xe = ub.iter().flatten().cloned().collect();
             self.ie = D as i32 -1;
             Ok(self)
         } else {
-            Err(FitError(204).into())
+            Err(FitError::TooManyConstraints { routine: "concur", max: (K+1)/2+1, found: D })
         }
     }
 
-    fn concur(&mut self, iopt:i32, e_rms:Option<f64>, knots: Option<Vec<f64>>) ->  i32 {
+    /**
+     Interior constraints, as `(u, order, value)` tuples: the derivative of the given order, or the position for
+     order 0, of the curve at parameter value `u` should be equal to `value`, for example to have the curve pass
+     through way points, with prescribed tangents.
+
+     The parameter values should be within the range of u, and the orders at most K. The constraints are applied to
+     the results of all fit methods, refitting the coefficients on the knots calculated by `concur`; see the
+     [`interior`](crate::interior) module.
+     */
+    pub fn interior_constraints(mut self, constraints: Vec<InteriorConstraint<N>>) -> FitResult<Self> {
+        interior::check("concur", &constraints, K, [self.u[0], self.u[self.u.len()-1]])?;
+        self.constraints = constraints;
+        Ok(self)
+    }
+
+    /// Calculates the `edf` of smoothing fits, see [`select`](crate::select#effective-degrees-of-freedom).
+    pub fn with_edf(mut self) -> Self {
+        self.edf = true;
+        self
+    }
+
+    fn concur(&mut self, iopt:i32, e_rms:Option<f64>, knots: Option<Vec<f64>>) -> FitResult<()> {
         let mut fp = 0.0;
         let s = if let Some(e) = e_rms {
             self.m as f64 * e.powi(2)
@@ -201,48 +244,108 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
        // self.tc.t.truncate(n); //todo to in from
        // self.tc.c.truncate(n);
        self.e_rms = Some((fp/self.m as f64).sqrt());
-       ierr
+       self.ier = ierr;
+       if ierr<=0 {
+           Ok(())
+       } else {
+           Err(FitError::from_ier("concur", ierr, self.nest, s))
+       }
     }
 
 
+    // constrained spline and fit report of the last fit, with `iterations` the number of fits calculated; the
+    // coefficients fixed by the end point constraints are not counted as free in the report; the arrays are returned
+    // to the work space, if any, when `self` is dropped
+    fn fitted(mut self, iterations: usize, edf: Edf) -> FitResult<FittedCurve<K,N>> {
+        let spline = self.constrained_spline(matches!(edf, Edf::Smoothing(_)))?;
+        let smoother = Smoother {
+            routine: "concur",
+            u: &self.u,
+            xn: &self.xn,
+            w: &self.w,
+            fixed: (self.ib as usize, self.ie as usize),
+            periodic: false,
+            constraints: self.constraints.len(),
+        };
+        let report = smoother.report(&spline, self.ier, iterations, edf);
+        Ok(FittedCurve { spline, report })
+    }
+
+    // spline of the last fit, with copies of the knots and coefficients if the arrays belong to a work space
+    fn take_spline(&mut self) -> SplineCurve<K,N> {
+        let n = self.n as usize;
+        if self.workspace.is_some() {
+            let c = (0..N).flat_map(|d| self.c[d * n..d * n + n - K - 1].iter().copied()).collect();
+            return SplineCurve::new(self.t[..n].to_vec(), c);
+        }
+        let (mut t, mut c) = (take(&mut self.t), take(&mut self.c));
+        t.truncate(n);
+        t.shrink_to_fit();
+
+        c.truncate(n * N); // this is the size as returned, but this conains K+1 unused values at the end
+        for dim in 0..N {
+            let ib = (dim+1) * (n-K-1);
+            let ie = ib + K + 1;
+            c.drain(ib..ie);
+        }
+        c.shrink_to_fit();
+        SplineCurve::new(t, c)
+    }
+
+    // spline of the last fit, with the interior constraints applied, together with the end point constraints
+    // enforced by `concur`: the derivatives of order 0..ib at the begin point, and 0..ie at the end point; for a
+    // `smoothing` fit, its sum of squared residuals is kept, unless `concur` returned a least squares spline
+    fn constrained_spline(&mut self, smoothing: bool) -> FitResult<SplineCurve<K,N>> {
+        let mut spline = self.take_spline();
+        if self.constraints.is_empty() {
+            return Ok(spline);
+        }
+        let (u, w) = (&self.u, &self.w);
+        let end_point = |v: f64, values: &[f64], count: i32| -> Vec<InteriorConstraint<N>> {
+            values.chunks(N).take(count as usize).enumerate().map(|(order, x)| (v, order, x.try_into().unwrap())).collect()
+        };
+        let constraints: Vec<InteriorConstraint<N>> = self.constraints.iter().copied()
+            .chain(end_point(u[0], &self.xb, self.ib))
+            .chain(end_point(u[u.len()-1], &self.xe, self.ie))
+            .collect();
+        let fp = (smoothing && self.ier == 0).then(|| self.m as f64 * self.e_rms.unwrap_or_default().powi(2));
+        interior::constrain("concur", &mut spline, (u, &self.xn, w), &constraints, fp)?;
+        Ok(spline)
+    }
+
     /**
      * Cardinal Spline: Weighted least squares spline with equidistant knots
      * 
      * Returns Spline, and rms error, with knots dt (input parameter) apart,
      * and aligned to integer multiples of it. Knots cover the range within
-     * the bounds of x.
+     * the bounds of u.
      */
-    pub fn cardinal_spline(mut self, dt:f64) -> Result<SplineCurve<K,N>>{
-        let m = self.u.len();
-        let tb = ((self.u[0]*(1.0+f64::EPSILON))/dt).ceil() * dt; // inner knots should bot be equal
-        let te = ((self.u[m-1]*(1.0-f64::EPSILON))/dt).floor() * dt;
-        let n = ((te - tb)/dt).round() as usize;
-        if n == 0 { return Err(FitError(205).into()) };
-        let mut t: Vec<f64> = Vec::with_capacity(n + 2 * (K + 1) + 1);
-        t.extend( repeat(tb).take(K+1) // begin padding, needed for spline evaluation
-            .chain(
-                repeat(dt).scan(tb, 
-                    |s, dx|{
-                        let t=*s; 
-                        *s+=dx; 
-                        if t<=te {
-                            Some(t)
-                        } else {
-                            None
-                        }
-                    })
-            )
-            .chain(
-                repeat(te).take(K+1) // end padding
-            )
-        );
-
-        let ierr = self.concur(-1, Some(0.0),Some(t));
-        if ierr <= 0 {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
-        }
+    pub fn cardinal_spline(mut self, dt:f64) -> FitResult<FittedCurve<K,N>>{
+        let (ub, ue) = (self.u[0], self.u[self.u.len()-1]);
+        let knots = cardinal_knots("concur", ub, ue, dt)?;
+        let t: Vec<f64> = repeat_n(ub, K+1) // begin padding, needed for spline evaluation
+            .chain(knots)
+            .chain(repeat_n(ue, K+1)) // end padding
+            .collect();
+
+        self.concur(-1, Some(0.0),Some(t))?;
+        self.fitted(1, Edf::LeastSquares)
+    }
+
+    /**
+     * Least Squares Spline: Weighted least squares spline with given interior knots
+     * 
+     * The interior knots should be in strictly ascending order, inside the range of u, with enough data points
+     * between them to determine all the B-spline coefficients (the Schoenberg-Whitney conditions).
+     * The boundary knots are added at the first and last value of u.
+     */
+    pub fn least_squares_spline(mut self, knots: Vec<f64>) -> FitResult<FittedCurve<K,N>> {
+        let (ub, ue) = (self.u[0], self.u[self.u.len()-1]);
+        validate::interior_knots("concur", &knots, ub, ue)?;
+        let t: Vec<f64> = repeat_n(ub, K+1).chain(knots).chain(repeat_n(ue, K+1)).collect();
+        validate::schoenberg_whitney("concur", &t, K, &self.u)?;
+        self.concur(-1, Some(0.0), Some(t))?;
+        self.fitted(1, Edf::LeastSquares)
     }
 
     /**
@@ -250,35 +353,28 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
       
       
      */ 
-    pub fn interpolating_spline(mut self) -> Result<SplineCurve<K,N>> {
-        let ierr = self.concur(0, Some(0.0),None);
-        if ierr<=0  {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
-        }
+    pub fn interpolating_spline(mut self) -> FitResult<FittedCurve<K,N>> {
+        self.concur(0, Some(0.0),None)?;
+        self.fitted(1, Edf::LeastSquares)
     }
 
     /**
      * Fit a smoothing spline
      * 
-     * nrguments:
+     * Arguments:
      * - rms: root mean square error
      * 
      */
-    pub fn smoothing_spline(mut self, rms: f64) -> Result<SplineCurve<K,N>>{
-        let ierr= self.concur(0, Some(rms), None);
-        if ierr>0 {
-            Err(FitError(ierr).into())
-        } else {
-            Ok(self.into())
-        }
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<FittedCurve<K,N>>{
+        self.concur(0, Some(rms), None)?;
+        let edf = Edf::Smoothing(self.edf);
+        self.fitted(1, edf)
     }
 
     /**
      * Fit a best-fit smoothing spline by decreasing rms target
      * 
-     * nrguments:
+     * Arguments:
      * - rms_start: root mean square error start value
      * - rms_scale_ratio: `rms *= rms_scale_ratio` for each step
      * - converged: boolean convergence function, with arguments
@@ -295,35 +391,74 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
             rms_scale_ratio: f64, 
             converged: impl Fn(i32, i32, f64, f64) -> bool, 
             n_iter: Option<usize>,
-        ) -> Result<SplineCurve<K,N>>{
+        ) -> FitResult<FittedCurve<K,N>>{
         let n_iter = n_iter.unwrap_or(40);
-        let ierr= self.concur(0, Some(rms_start), None);
-        if ierr>0 {
-            return Err(FitError(ierr).into())
-        }
+        self.concur(0, Some(rms_start), None)?;
         let mut rms = self.e_rms.unwrap();
         let mut n_prev;
         let mut rms_prev ;
-        for _ in 0..n_iter {
+        for i in 0..n_iter {
             n_prev = self.n;
             rms_prev = rms;
-            let ierr= self.concur(1, Some(rms * rms_scale_ratio), None);
+            self.concur(1, Some(rms * rms_scale_ratio), None)?;
             rms = self.e_rms.unwrap();
-            if ierr>0 {
-                return Err(FitError(ierr).into())
-            }
             if converged(self.n, self.n-n_prev, rms, rms_prev - rms) { // finishing fit
-                let ierr = self.concur(0, Some(rms_prev), None);
-                if ierr>0 {
-                    return Err(FitError(ierr).into())
-                } else {
-                    return Ok(self.into())
-                }
+                self.concur(0, Some(rms_prev), None)?;
+                let edf = Edf::Smoothing(self.edf);
+                return self.fitted(i + 3, edf)
             };
         }
-        Err(FitError(206).into())
+        Err(FitError::NotConverged { routine: "concur", iterations: n_iter })
+    }
+
+    /**
+     * Constrained curve, with the target rms value selected from a set of candidate values
+     * 
+     * Fits a `concur` smoothing curve for each of the target rms values, with the end point derivative constraints,
+     * and the interior constraints, applied to every candidate. The coefficients fixed by the end point constraints
+     * are not counted in the effective degrees of freedom of the scores; see the [`select`](crate::select) module.
+     * 
+     * Arguments:
+     * - rms: target root mean square error values, for example from [`rms_range`](crate::rms_range)
+     * - criterion: selection criterion
+     *
+     */
+    pub fn select_smoothing(&self, rms: &[f64], criterion: Criterion) -> FitResult<SmoothingSelection<K,N>> {
+        let smoother = Smoother {
+            routine: "concur",
+            u: &self.u,
+            xn: &self.xn,
+            w: &self.w,
+            fixed: (self.ib as usize, self.ie as usize),
+            periodic: false,
+            constraints: self.constraints.len(),
+        };
+        smoother.select(rms, criterion, |rms| {
+            let mut fit = self.clone();
+            fit.concur(0, Some(rms), None)?;
+            fit.constrained_spline(true)
+        })
     }
 
+    /**
+     * Robust Smoothing Spline
+     *
+     * Constrained smoothing curve, refitted with the weights of the data points reduced for large distances
+     * between the points and the curve, until the robustness weights converge; see the [`robust`](crate::robust)
+     * module. A point is down-weighted in all its N coordinates at once. The end point and interior constraints
+     * are applied in every refit, and are not affected by the robustness weights.
+     *
+     * Arguments:
+     * - rms: root mean square error, of the weighted distances
+     * - loss: loss function, for example `RobustLoss::huber()` or `RobustLoss::bisquare()`
+     * - n_iter: maximum number of reweighted fits, 20 if `None`
+     *
+     */
+    pub fn robust_smoothing_spline(&self, rms: f64, loss: RobustLoss, n_iter: Option<usize>) -> FitResult<RobustFit<K,N>> {
+        reweighted_fit("concur", self, &self.w, loss, n_iter, Self::weights, |fit| fit.smoothing_spline(rms))
+    }
+
+
 
     
 
@@ -333,23 +468,34 @@ impl<const K:usize, const N:usize> ParameterSplineCurveFit<K, N> {
 
 impl<const K:usize, const N:usize> From<ParameterSplineCurveFit<K,N>> for SplineCurve<K,N> {
     fn from(mut sp: ParameterSplineCurveFit<K,N>) -> Self {
-        sp.t.truncate(sp.n as usize);
-        sp.t.shrink_to_fit();
-
-        sp.c.truncate((sp.n*sp.idim) as usize); // this is the size as returned, but this conains K+1 unused values at the end
+        sp.take_spline()
+    }
+}
 
-        for dim in 0..sp.idim as usize {
-            let ib = (dim+1) * (sp.n-sp.k-1) as usize;
-            let ie = ib + sp.k as usize + 1;
-            sp.c.drain(ib..ie);
+// a clone has its own arrays, and does not return them to the work space, as these would replace the arrays of the
+// fitter it was cloned from, when it is dropped
+impl<const K:usize, const N:usize> Clone for ParameterSplineCurveFit<K,N> {
+    fn clone(&self) -> Self {
+        Self {
+            xn: self.xn.clone(), u: self.u.clone(), w: self.w.clone(), xb: self.xb.clone(), xe: self.xe.clone(),
+            constraints: self.constraints.clone(), t: self.t.clone(), c: self.c.clone(),
+            e_rms: self.e_rms, ier: self.ier, edf: self.edf, n: self.n,
+            wrk: self.wrk.clone(), iwrk: self.iwrk.clone(), xx: self.xx.clone(), cp: self.cp.clone(),
+            ib: self.ib, ie: self.ie, m: self.m, mx: self.mx, nest: self.nest, k: self.k, idim: self.idim,
+            workspace: None,
         }
-        sp.c.shrink_to_fit();
-
-        Self::new(
-            sp.t,
-            sp.c,
+    }
+}
 
-        )
+impl<const K:usize, const N:usize> Drop for ParameterSplineCurveFit<K,N> {
+    // returns the arrays to the work space, if any, also if the fit failed
+    fn drop(&mut self) {
+        if let Some(workspace) = self.workspace.take() {
+            workspace.give(Buffers {
+                t: take(&mut self.t), c: take(&mut self.c), wrk: take(&mut self.wrk), iwrk: take(&mut self.iwrk),
+                xx: take(&mut self.xx), cp: take(&mut self.cp), w: take(&mut self.w),
+            });
+        }
     }
 }
 
@@ -1,8 +1,12 @@
-use std::iter::repeat;
+use std::iter::repeat_n;
 use dierckx_sys::{curfit_};
 use spliny::SplineCurve;
-use super::{FitError};
-use crate::Result;
+use super::{validate, Criterion, FitError, FitResult, FittedCurve, SmoothingSelection};
+use std::mem::take;
+use crate::monotone::{monotone_coefficients, Monotonicity};
+use crate::robust::{reweighted_fit, RobustFit, RobustLoss};
+use crate::select::{Edf, Smoother};
+use crate::workspace::{sized, Buffers, FitWorkspace};
 
 
 pub struct SplineCurveFit<const K:usize> {
@@ -10,16 +14,21 @@ pub struct SplineCurveFit<const K:usize> {
     x: Vec<f64>,    // data x coordinates
     y: Vec<f64>,    // data y coordinates
     w: Vec<f64>,    // weight factors, 
+    xb: f64,        // begin of the approximation interval
+    xe: f64,        // end of the approximation interval
 
     pub t: Vec<f64>,
     pub c: Vec<f64>,
     pub n: i32,
 
     e_rms: Option<f64>,
+    ier: i32,
+    edf: bool,      // calculate the effective degrees of freedom of smoothing fits
 
     // work space values
     wrk: Vec<f64>,  // used for successive tries
     iwrk: Vec<i32>, // used for successive tries
+    workspace: Option<FitWorkspace>, // to return the arrays to after the fit
 }
 
 
@@ -28,39 +37,82 @@ impl<const K:usize> SplineCurveFit<K> {
     /**
      Constructor, with inputs x and y vectors, and an optional weights vectors.
 
-     The vectors should have equal length.
+     The vectors should have equal length, with finite values, and x in strictly ascending order.
+     At least K+1 data points are required.
      */
-    pub fn new(x: Vec<f64>, y: Vec<f64>) -> Self {
+    pub fn new(x: Vec<f64>, y: Vec<f64>) -> FitResult<Self> {
+        Self::build(x, y, None)
+    }
 
-        let m = x.len();
-        let w_vec = vec![1.0; m];
-        assert!(y.len()==m);
-        assert!(w_vec.len()==y.len());
+    /**
+     Constructor, as `new`, using the arrays kept in a work space, and returning them to it after the fit.
+
+     Use this to fit many data sets in sequence, without allocating the work arrays for each of them; see the
+     [`workspace`](crate::workspace) module.
+     */
+    pub fn with_workspace(x: Vec<f64>, y: Vec<f64>, workspace: &FitWorkspace) -> FitResult<Self> {
+        Self::build(x, y, Some(workspace.clone()))
+    }
 
+    fn build(x: Vec<f64>, y: Vec<f64>, workspace: Option<FitWorkspace>) -> FitResult<Self> {
+
+        let m = x.len();
+        if !(1..=5).contains(&K) { return Err(FitError::InvalidDegree { routine: "curfit", k: K, allowed: "1 to 5" }) };
+        if m <= K { return Err(FitError::TooFewDataPoints { routine: "curfit", found: m, min: K + 1 }) };
+        validate::size("curfit", "y", &y, m)?;
+        validate::strictly_ascending("curfit", "x", &x)?;
+        validate::finite("curfit", "y", &y)?;
+        let buffers = workspace.as_ref().map(FitWorkspace::take).unwrap_or_default();
+        let mut w_vec = sized(buffers.w, m);
+        w_vec.fill(1.0);
         let nest = m * K  + 1;
-        let t_vec = vec![0.0;nest];
-        let c_vec = vec![0.0;nest];
+        let t_vec = sized(buffers.t, nest);
+        let c_vec = sized(buffers.c, nest);
         let n = nest as i32;
 
-        let iwrk_vec = vec![0i32; nest];
+        let iwrk_vec = sized(buffers.iwrk, nest);
 
         let lwrk = m * (K + 1) + nest * (7 + 3 * K);
-        let wrk_vec = vec![0f64; lwrk];
+        let wrk_vec = sized(buffers.wrk, lwrk);
 
-        Self { x, y, w: w_vec, t: t_vec, c: c_vec, n, wrk: wrk_vec, iwrk: iwrk_vec, e_rms: None}
+        let (xb, xe) = (x[0], x[m-1]);
+        Ok(Self { x, y, w: w_vec, xb, xe, t: t_vec, c: c_vec, n, wrk: wrk_vec, iwrk: iwrk_vec, e_rms: None, ier: 0, edf: false, workspace})
 
     }
 
-    pub fn set_weights(mut self, weights:Vec<f64>) -> Result<Self> {
-        if weights.len() == self.x.len() {
-            self.w = weights;
-            Ok(self)
-        } else {
-            Err(FitError(203).into())
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn set_weights(mut self, weights:Vec<f64>) -> FitResult<Self> {
+        validate::weights("curfit", &weights, self.x.len())?;
+        self.w = weights;
+        Ok(self)
+    }
+
+    /**
+     Sets the approximation interval `[xb, xe]`, which defaults to the range of the x values.
+
+     The interval should contain all the x values; use this to get a spline which extends beyond the range of
+     the data, for example to cover a standard wavelength range. The boundary knots are placed at `xb` and `xe`.
+     */
+    pub fn set_interval(mut self, xb: f64, xe: f64) -> FitResult<Self> {
+        let (x0, xm) = (self.x[0], self.x[self.x.len()-1]);
+        if !xb.is_finite() || !xe.is_finite() || xb > x0 || xe < xm {
+            return Err(FitError::InvalidInput {
+                routine: "curfit",
+                reason: format!("the interval [{xb}, {xe}] should be finite, and contain the range [{x0}, {xm}] of x"),
+            });
         }
+        self.xb = xb;
+        self.xe = xe;
+        Ok(self)
+    }
+
+    /// Calculates the `edf` of smoothing fits, see [`select`](crate::select#effective-degrees-of-freedom).
+    pub fn with_edf(mut self) -> Self {
+        self.edf = true;
+        self
     }
 
-    fn curfit(&mut self, iopt:i32, e_rms:Option<f64>, knots: Option<Vec<f64>>) ->  i32 {
+    fn curfit(&mut self, iopt:i32, e_rms:Option<f64>, knots: Option<Vec<f64>>) -> FitResult<()> {
         let k = K as i32;
         let m = self.x.len() as i32;
         let nest = m * k  + 1;
@@ -80,7 +132,7 @@ impl<const K:usize> SplineCurveFit<K> {
         unsafe {
             curfit_(&iopt, &m, 
                 self.x.as_ptr(), self.y.as_ptr(), self.w.as_ptr(), 
-                &self.x[0], &self.x[m as usize -1], 
+                &self.xb, &self.xe, 
                 &k, &s, &nest, &mut self.n, 
                 self.t.as_mut_ptr(), self.c.as_mut_ptr(), 
                 &mut fp, 
@@ -89,7 +141,12 @@ impl<const K:usize> SplineCurveFit<K> {
             );
         }
         self.e_rms = Some((fp/m as f64).sqrt());
-        ierr
+        self.ier = ierr;
+        if ierr<=0 {
+            Ok(())
+        } else {
+            Err(FitError::from_ier("curfit", ierr, nest, s))
+        }
     }
 
 
@@ -98,53 +155,65 @@ impl<const K:usize> SplineCurveFit<K> {
      * 
      * Returns Spline, and rms error, with knots dt (input parameter) apart,
      * and aligned to integer multiples of it. Knots cover the range within
-     * the bounds of x.
+     * the approximation interval, by default the bounds of x.
      */
-    pub fn cardinal_spline(mut self, dt:f64) -> Result<SplineCurve<K,1>>{
-        let m = self.x.len();
-        let tb = (self.x[0]/dt).ceil() * dt;
-        let te = (self.x[m-1]/dt).floor() * dt;
-        let n = ((te - tb)/dt).round() as usize;
-        if n == 0 { return Err(FitError(205).into())};
-
-        let mut t: Vec<f64> = Vec::with_capacity(n + 2 * (K + 1) + 1);
-        t.extend( repeat(tb).take(K+1) // begin padding, needed for spline evaluation
-            .chain(
-                repeat(dt).scan(tb, 
-                    |s, dx|{
-                        let t=*s; 
-                        *s+=dx; 
-                        if t<=te {
-                            Some(t)
-                        } else {
-                            None
-                        }
-                    })
-            )
-            .chain(
-                repeat(te).take(K+1) // end padding
-            )
-        );
-
-
-        let ierr = self.curfit(-1, Some(0.0),Some(t));
-        if ierr<=0  {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
+    pub fn cardinal_spline(mut self, dt:f64) -> FitResult<FittedCurve<K,1>>{
+        let knots = cardinal_knots("curfit", self.xb, self.xe, dt)?;
+        let t: Vec<f64> = repeat_n(self.xb, K+1) // begin padding, needed for spline evaluation
+            .chain(knots)
+            .chain(repeat_n(self.xe, K+1)) // end padding
+            .collect();
+
+        self.curfit(-1, Some(0.0),Some(t))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
+    }
+
+    // spline and fit report of the last fit, with `iterations` the number of fits calculated, and `edf` the effective
+    // degrees of freedom to report; the arrays are returned to the work space, if any, when `self` is dropped
+    fn fitted(mut self, iterations: usize, edf: Edf) -> FittedCurve<K,1> {
+        let spline = self.take_spline();
+        let smoother = Smoother { routine: "curfit", u: &self.x, xn: &self.y, w: &self.w, fixed: (0, 0), periodic: false, constraints: 0 };
+        let report = smoother.report(&spline, self.ier, iterations, edf);
+        FittedCurve { spline, report }
+    }
+
+    // spline of the last fit, with copies of the knots and coefficients if the arrays belong to a work space
+    fn take_spline(&mut self) -> SplineCurve<K,1> {
+        let n = self.n as usize;
+        if self.workspace.is_some() {
+            return SplineCurve::new(self.t[..n].to_vec(), self.c[..n - K - 1].to_vec());
         }
+        let (mut t, mut c) = (take(&mut self.t), take(&mut self.c));
+        t.truncate(n); // n number of required knots
+        t.shrink_to_fit();
+        c.truncate(n - (K + 1)); // this is the size as returned, but this conains K+1 unused values at the end
+        c.shrink_to_fit();
+        SplineCurve::new(t, c)
+    }
+
+    /**
+     * Least Squares Spline: Weighted least squares spline with given interior knots
+     * 
+     * The interior knots should be in strictly ascending order, inside the approximation interval, with enough data
+     * points between them to determine all the B-spline coefficients (the Schoenberg-Whitney conditions).
+     * The boundary knots are added at the bounds of the approximation interval, by default the first and last value
+     * of x.
+     */
+    pub fn least_squares_spline(mut self, knots: Vec<f64>) -> FitResult<FittedCurve<K,1>> {
+        let (xb, xe) = (self.xb, self.xe);
+        validate::interior_knots("curfit", &knots, xb, xe)?;
+        let t: Vec<f64> = repeat_n(xb, K+1).chain(knots).chain(repeat_n(xe, K+1)).collect();
+        validate::schoenberg_whitney("curfit", &t, K, &self.x)?;
+        self.curfit(-1, Some(0.0), Some(t))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
     }
 
     /**
      Interpolating Spline
      */ 
-    pub fn interpolating_spline(mut self) -> Result<SplineCurve<K,1>> {
-        let ierr = self.curfit(0, Some(0.0),None);
-        if ierr<=0  {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
-        }
+    pub fn interpolating_spline(mut self) -> FitResult<FittedCurve<K,1>> {
+        self.curfit(0, Some(0.0),None)?;
+        Ok(self.fitted(1, Edf::LeastSquares))
     }
 
     /**
@@ -153,13 +222,206 @@ impl<const K:usize> SplineCurveFit<K> {
      * A spline with a minimal number of knots, with error less than the specifed rms value.
      * Repeat fit with smaller rms value using `smooth_more`.
      */
-    pub fn smoothing_spline(mut self, rms: f64) -> Result<SplineCurve<K,1>>{
-        let ierr = self.curfit(0, Some(rms), None);
-        if ierr<=0  {
-            Ok(self.into())
-        } else {
-            Err(FitError(ierr).into())
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<FittedCurve<K,1>>{
+        self.curfit(0, Some(rms), None)?;
+        let edf = Edf::Smoothing(self.edf);
+        Ok(self.fitted(1, edf))
+    }
+
+    /**
+     * Monotone Spline: Non-decreasing or non-increasing spline
+     * 
+     * Uses the knots of the smoothing spline with error less than the specified rms value, or of the interpolating
+     * spline for `rms=0`, and fits the B-spline coefficients by weighted least squares, constrained to be in
+     * ascending or descending order, which makes the spline monotone over the approximation interval.
+     * The rms error of the fit is larger than `rms` if the smoothing spline is not monotone.
+     */
+    pub fn monotone_spline(mut self, rms: f64, monotonicity: Monotonicity) -> FitResult<FittedCurve<K,1>>{
+        self.curfit(0, Some(rms), None)?;
+        let n = self.n as usize;
+        let c = monotone_coefficients("curfit", &self.t[..n], K, (&self.x, &self.y, &self.w), monotonicity)?;
+        self.c[..c.len()].copy_from_slice(&c);
+        self.ier = 0; // the warnings of curfit apply to the smoothing spline, not to the monotone spline
+        Ok(self.fitted(1, Edf::None))
+    }
+
+    /**
+     * Smoothing Session
+     * 
+     * Fits a smoothing spline, as `smoothing_spline`, but returns a session which keeps the knots and workspace of
+     * `curfit`, to repeat the fit with smaller rms values using `smooth_more`.
+     */
+    pub fn smoothing_session(mut self, rms: f64) -> FitResult<SmoothingSession<K>>{
+        self.curfit(0, Some(rms), None)?;
+        let nest = self.t.len();
+        let backup = SessionBackup {
+            t: vec![0.0; nest],
+            c: vec![0.0; nest],
+            wrk: vec![0.0; nest],
+            iwrk: vec![0; nest],
+            n: 0,
+            e_rms: None,
+        };
+        let mut session = SmoothingSession { fit: self, steps: Vec::new(), backup };
+        session.push_step(rms);
+        Ok(session)
+    }
+
+    /**
+     * Fit a best-fit smoothing spline by decreasing rms target
+     * 
+     * Arguments:
+     * - rms_start: root mean square error start value
+     * - rms_scale_ratio: `rms *= rms_scale_ratio` for each step
+     * - converged: boolean convergence function, with arguments
+     *   - number of knots (usize),
+     *   - number of added knots in the last iteration (usize)
+     *   - root mean square error of the fit (f64),
+     *   - root mean square error improvement in the last iteration
+     * - n_iter: number of iterations
+     *   
+     */
+    pub fn smoothing_spline_optimize(mut self, 
+            rms_start: f64, 
+            rms_scale_ratio: f64, 
+            converged: impl Fn(i32, i32, f64, f64) -> bool, 
+            n_iter: Option<usize>,
+        ) -> FitResult<FittedCurve<K,1>>{
+        let n_iter = n_iter.unwrap_or(40);
+        self.curfit(0, Some(rms_start), None)?;
+        let mut rms = self.e_rms.unwrap();
+        let mut n_prev;
+        let mut rms_prev ;
+        for i in 0..n_iter {
+            n_prev = self.n;
+            rms_prev = rms;
+            self.curfit(1, Some(rms * rms_scale_ratio), None)?;
+            rms = self.e_rms.unwrap();
+            if converged(self.n, self.n-n_prev, rms, rms_prev - rms) { // finishing fit
+                self.curfit(0, Some(rms_prev), None)?;
+                let edf = Edf::Smoothing(self.edf);
+                return Ok(self.fitted(i + 3, edf))
+            };
+        }
+        Err(FitError::NotConverged { routine: "curfit", iterations: n_iter })
+    }
+
+    /**
+     * Smoothing spline, with the target rms value selected from a set of candidate values
+     * 
+     * Fits a `curfit` smoothing spline y(x) for each of the target rms values, with the weights and interval of this
+     * fit, and returns the candidate with the best score; see the [`select`](crate::select) module for the scores.
+     * 
+     * Arguments:
+     * - rms: target root mean square error values, for example from [`rms_range`](crate::rms_range)
+     * - criterion: selection criterion
+     *
+     */
+    pub fn select_smoothing(&self, rms: &[f64], criterion: Criterion) -> FitResult<SmoothingSelection<K,1>> {
+        let smoother = Smoother { routine: "curfit", u: &self.x, xn: &self.y, w: &self.w, fixed: (0, 0), periodic: false, constraints: 0 };
+        smoother.select(rms, criterion, |rms| {
+            let mut fit = self.clone();
+            fit.curfit(0, Some(rms), None)?;
+            Ok(fit.into())
+        })
+    }
+
+    /**
+     * Robust Smoothing Spline
+     *
+     * Smoothing spline y(x), refitted with the weights of the data points reduced for large residuals
+     * `y(i) - s(x(i))`, until the robustness weights converge; see the [`robust`](crate::robust) module.
+     * The data points with a robustness weight below 0.5 are listed as outliers.
+     *
+     * Arguments:
+     * - rms: root mean square error, of the weighted residuals
+     * - loss: loss function, for example `RobustLoss::huber()` or `RobustLoss::bisquare()`
+     * - n_iter: maximum number of reweighted fits, 20 if `None`
+     *
+     */
+    pub fn robust_smoothing_spline(&self, rms: f64, loss: RobustLoss, n_iter: Option<usize>) -> FitResult<RobustFit<K,1>> {
+        reweighted_fit("curfit", self, &self.w, loss, n_iter, Self::set_weights, |fit| fit.smoothing_spline(rms))
+    }
+}
+
+/// A step of a [`SmoothingSession`]: the target rms value, the rms error obtained, and the knots of the fit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothingStep {
+    pub rms: f64,
+    pub e_rms: f64,
+    pub t: Vec<f64>,
+}
+
+/**
+
+ Smoothing fit session, obtained from [`SplineCurveFit::smoothing_session`]
+
+ Repeats `curfit` smoothing fits with `iopt=1`, starting from the knots of the previous fit, which is faster than a
+ new fit, and only adds knots. Use decreasing rms values; the knots and rms errors of all the steps are available
+ by `steps`.
+
+ */
+pub struct SmoothingSession<const K:usize> {
+    fit: SplineCurveFit<K>,
+    steps: Vec<SmoothingStep>,
+    backup: SessionBackup,
+}
+
+// State of the last successful step, restored when a step fails. Of the work space, `curfit` only needs the first
+// `n` values of `wrk` and `iwrk` for a fit with `iopt=1`, which are within the first `nest` values.
+struct SessionBackup {
+    t: Vec<f64>,
+    c: Vec<f64>,
+    wrk: Vec<f64>,
+    iwrk: Vec<i32>,
+    n: i32,
+    e_rms: Option<f64>,
+}
+
+impl<const K:usize> SmoothingSession<K> {
+
+    fn push_step(&mut self, rms: f64) {
+        let t = self.fit.t[..self.fit.n as usize].to_vec();
+        self.steps.push(SmoothingStep { rms, e_rms: self.fit.e_rms.unwrap_or(0.0), t });
+    }
+
+    /// Repeats the fit with a smaller rms value, reusing the knots of the previous fit, and returns the result
+    /// of this step. On error the session keeps the results of the previous step.
+    pub fn smooth_more(&mut self, rms: f64) -> FitResult<&SmoothingStep> {
+        let (fit, backup) = (&mut self.fit, &mut self.backup);
+        let nest = backup.t.len();
+        backup.t.copy_from_slice(&fit.t);
+        backup.c.copy_from_slice(&fit.c);
+        backup.wrk.copy_from_slice(&fit.wrk[..nest]);
+        backup.iwrk.copy_from_slice(&fit.iwrk[..nest]);
+        (backup.n, backup.e_rms) = (fit.n, fit.e_rms);
+        if let Err(e) = fit.curfit(1, Some(rms), None) {
+            fit.t.copy_from_slice(&backup.t);
+            fit.c.copy_from_slice(&backup.c);
+            fit.wrk[..nest].copy_from_slice(&backup.wrk);
+            fit.iwrk[..nest].copy_from_slice(&backup.iwrk);
+            (fit.n, fit.e_rms) = (backup.n, backup.e_rms);
+            return Err(e);
         }
+        self.push_step(rms);
+        Ok(self.steps.last().unwrap())
+    }
+
+    /// Knots, target rms values, and rms errors of all the fits in this session, with the first fit first.
+    pub fn steps(&self) -> &[SmoothingStep] {
+        &self.steps
+    }
+
+    /// Spline of the last fit; use `into` to finalize the session.
+    pub fn spline(&self) -> SplineCurve<K,1> {
+        let n = self.fit.n as usize;
+        SplineCurve::new(self.fit.t[..n].to_vec(), self.fit.c[..n - (K + 1)].to_vec())
+    }
+}
+
+impl<const K:usize> From<SmoothingSession<K>> for SplineCurve<K,1> {
+    fn from(session: SmoothingSession<K>) -> Self {
+        session.fit.into()
     }
 }
 
@@ -181,19 +443,47 @@ impl<const K:usize> From<CurveSplineFit<K>> for Spline<K,1> {
 }
 */
 
+/// Interior knots of a cardinal spline, `dt` apart, and aligned to integer multiples of it, inside the open interval
+/// `(xb, xe)`; used by the `cardinal_spline` methods of all curve fits.
+pub(crate) fn cardinal_knots(routine: &'static str, xb: f64, xe: f64, dt: f64) -> FitResult<Vec<f64>> {
+    let mut tb = (xb/dt).ceil() * dt;
+    let mut te = (xe/dt).floor() * dt;
+    // interior knots should not coincide with the bounds of the interval
+    if tb <= xb { tb += dt };
+    if te >= xe { te -= dt };
+    if te < tb { return Err(FitError::KnotSpacing { routine, dt })};
+    let n = ((te - tb)/dt).round() as usize;
+    Ok((0..=n).map(|i| tb + i as f64 * dt).collect())
+}
+
 impl<const K:usize> From<SplineCurveFit<K>> for SplineCurve<K,1> {
     fn from(mut sp: SplineCurveFit<K>) -> Self {
-        sp.t.truncate(sp.n as usize); // sp.n number of required knots 
-        sp.t.shrink_to_fit();
-
-        sp.c.truncate(sp.n as usize - (K + 1)); // this is the size as returned, but this conains K+1 unused values at the end
-        sp.c.shrink_to_fit();
+        sp.take_spline()
+    }
+}
 
-        Self::new(
-            sp.t,
-            sp.c,
+// a clone has its own arrays, and does not return them to the work space, as these would replace the arrays of the
+// fitter it was cloned from, when it is dropped
+impl<const K:usize> Clone for SplineCurveFit<K> {
+    fn clone(&self) -> Self {
+        Self {
+            x: self.x.clone(), y: self.y.clone(), w: self.w.clone(), xb: self.xb, xe: self.xe,
+            t: self.t.clone(), c: self.c.clone(), n: self.n,
+            e_rms: self.e_rms, ier: self.ier, edf: self.edf,
+            wrk: self.wrk.clone(), iwrk: self.iwrk.clone(), workspace: None,
+        }
+    }
+}
 
-        )
+impl<const K:usize> Drop for SplineCurveFit<K> {
+    // returns the arrays to the work space, if any, also if the fit failed
+    fn drop(&mut self) {
+        if let Some(workspace) = self.workspace.take() {
+            workspace.give(Buffers {
+                t: take(&mut self.t), c: take(&mut self.c), wrk: take(&mut self.wrk), iwrk: take(&mut self.iwrk),
+                w: take(&mut self.w), ..Default::default()
+            });
+        }
     }
 }
 
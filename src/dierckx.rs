@@ -0,0 +1,109 @@
+//! Foreign Function Interface definitions for Dierckx routines compiled into `dierckx-sys`, but not (or not
+//! correctly) declared by it.
+
+use std::os::raw::{c_double, c_int};
+
+extern "C" {
+
+    // a single derivative for an array of points
+    pub fn splder_(
+        t: *const c_double,     // array,length n, which contains the position of the knots
+        n: *const c_int,        // integer, giving the total number of knots of s(x).
+        c: *const c_double,     // array,length n, which contains the b-spline coefficients
+        k: *const c_int,        // integer, degree of s(x)
+        nu: *const c_int,       // integer, specifying the order of the derivative. 0<=nu<=k
+        x: *const c_double,     // array,length m, which contains the points where the derivative of s(x) must be evaluated
+        y: *mut c_double,       // array,length m, giving the value of the derivative of s(x) at the different points
+        m: *const c_int,        // length of x
+        wrk: *mut c_double,     // Float working array of dimension n
+        ier: *mut c_int,        // ier = 0 : normal return;  ier =10 : invalid input data
+    );
+
+    // all derivatives of a spline curve for a single point; the `dierckx-sys` declaration lacks the `nc` argument
+    pub fn cualde_(
+        idim: *const c_int,     // integer, giving the dimension of the spline curve
+        t: *const c_double,     // array,length n, which contains the position of the knots
+        n: *const c_int,        // integer, giving the total number of knots of s(u).
+        c: *const c_double,     // array,length nc, which contains the b-spline coefficients
+        nc: *const c_int,       // integer, giving the total number of coefficients of s(u)
+        k1: *const c_int,       // integer, giving the order of s(u), k+1, with k polynomial degree
+        u: *const c_double,     // (single) f64 value which contains the point where the derivatives must be evaluated
+        d: *mut c_double,       // array,length nd; d(idim*l+j) will contain the j-th coordinate of the l-th derivative of the curve at the point u
+        nd: *const c_int,       // integer, giving the dimension of array d: `nd >=(k+1)*idim`
+        ier: *mut c_int,        // ier = 0 : normal return;  ier =10 : invalid input data
+    );
+
+    // parametric curve fit, with optional automatic parameterization
+    pub fn parcur_(
+        iopt: *const c_int,     // iopt -1: Least-squares spline fixed knots, 0,1: smoothing spline. iopt=0 and s=0: interpolating spline
+        ipar: *const c_int,     // ipar 1: User supplied parameter values u(i), 0: values will be calculated by parcur
+        idim: *const c_int,     // dimension of the curve 0 < idim < 11
+        m: *const c_int,        // Number of data points supplied
+        u: *mut c_double,       // Array of parameter values; with ipar=0 calculated by parcur on exit
+        mx: *const c_int,       // Array size of data points, idim * m
+        x: *const c_double,     // Datapoints eg [x0, y0, z0, ... x1, y1.., z1, ..]
+        w: *const c_double,     // Array weights (at least m values)
+        ub: *mut c_double,      // Lower bound of the parameter; with ipar=0 set to 0 by parcur
+        ue: *mut c_double,      // Upper bound of the parameter; with ipar=0 set to 1 by parcur
+        k: *const c_int,        // Degree of the spline, cubic = 3
+        s: *const c_double,     // Smoothing factor to be used if iopt >= 0
+        nest: *const c_int,     // nest >= m+k+1 for interpolation
+        n: *mut c_int,          // Number of knots returned. For iopt=-1 value needs to be specified on entry
+        t: *mut c_double,       // Array of dimension of at least nest. For iopt=-1 array of knots to be used for lsq spline
+        nc: *const c_int,       // actual size of array c: nest * idim
+        c: *mut c_double,       // coefficients of the b-spline representation, with size nc = nest * idim
+        fp: *mut c_double,      // Weighted sum of the squared residuals of the spline approximation.
+        wrk: *mut c_double,     // Double array of dimension at least m*(k+1)+nest*(6+idim+3*k).
+        lwrk: *const c_int,     // Size of 'wrk'
+        iwrk: *mut c_int,       // int Array of at least nest
+        ier: *mut c_int,        // Error flag.
+    );
+
+    // cubic spline fit with convexity constraints
+    pub fn concon_(
+        iopt: *const c_int,     // iopt 0: start with minimal number of knots, 1: continue with knots from last call
+        m: *const c_int,        // Number of data points supplied, m > 3
+        x: *const c_double,     // Array of x coordinates (at least m values)
+        y: *const c_double,     // Array of y coordinates (at least m values)
+        w: *const c_double,     // Array weights (at least m values)
+        v: *const c_double,     // Array of convexity constraints: 1 concave, -1 convex, 0 no constraint
+        s: *const c_double,     // Over-estimate of the weighted sum of squared residuals
+        nest: *const c_int,     // nest >= 8; always large enough is m+4
+        maxtr: *const c_int,    // Over-estimate of the number of records in the tree structure
+        maxbin: *const c_int,   // Over-estimate of the number of knots where s''(x) is zero
+        n: *mut c_int,          // Number of knots returned
+        t: *mut c_double,       // Array of dimension of at least nest, containing the knots on exit
+        c: *mut c_double,       // Array of dimension of at least nest, containing the b-spline coefficients on exit
+        sq: *mut c_double,      // Weighted sum of the squared residuals of the spline approximation.
+        sx: *mut c_double,      // Array of dimension m, containing the spline values at x on exit
+        bind: *mut c_int,       // Fortran logical array of dimension nest, indicating the knots where s''(x)=0
+        wrk: *mut c_double,     // Double array of dimension at least m*4+nest*8+maxbin*(maxbin+nest+1)
+        lwrk: *const c_int,     // Size of 'wrk'
+        iwrk: *mut c_int,       // int Array of dimension at least maxtr*4+2*(maxbin+1)
+        kwrk: *const c_int,     // Size of 'iwrk'
+        ier: *mut c_int,        // Error flag.
+    );
+
+    // definite integral, with s(x) considered to be zero outside the knot range
+    pub fn splint_(
+        t: *const c_double,     // array,length n, which contains the position of the knots
+        n: *const c_int,        // integer, giving the total number of knots of s(x).
+        c: *const c_double,     // array,length n, which contains the b-spline coefficients
+        k: *const c_int,        // integer, degree of s(x)
+        a: *const c_double,     // lower integration limit
+        b: *const c_double,     // upper integration limit
+        wrk: *mut c_double,     // Float working array of dimension n, contains the integrals of the b-splines on exit
+    ) -> c_double;
+
+    // zeros of a cubic spline
+    pub fn sproot_(
+        t: *const c_double,     // array,length n, which contains the position of the knots
+        n: *const c_int,        // integer, giving the total number of knots of s(x), n>=8
+        c: *const c_double,     // array,length n, which contains the b-spline coefficients
+        zero: *mut c_double,    // array,length mest, containing the zeros of s(x) on exit
+        mest: *const c_int,     // integer, dimension of array zero
+        m: *mut c_int,          // integer, giving the number of zeros found
+        ier: *mut c_int,        // ier = 0 : normal return; ier = 1: number of zeros exceeds mest; ier =10 : invalid input data
+    );
+
+}
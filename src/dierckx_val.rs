@@ -1,10 +1,10 @@
-
-use super::{FitError, Result};
+use super::{FitError, FitResult};
 use spliny::SplineCurve;
-use dierckx_sys::{splev_, curev_};
+use dierckx_sys::{splev_, curev_, spalde_};
+use crate::dierckx::{cualde_, splder_, splint_, sproot_};
 
 
-pub fn evaluate<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64]) -> Result<Vec<f64>> {
+pub fn evaluate<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64]) -> FitResult<Vec<f64>> {
     let (ierr, y)  = 
         match N {
             1  => splev(s, x),
@@ -13,8 +13,130 @@ pub fn evaluate<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64])
     if ierr<=0 {
         Ok(y)
     } else {
-        Err(FitError::new(ierr).into())
+        Err(FitError::from_ier(if N == 1 { "splev" } else { "curev" }, ierr, s.t.len() as i32, 0.0))
+    }
+}
+
+/**
+ Evaluates the derivative of order `order` of a spline at the parameter values `x`.
+
+ For `N=1` this wraps Dierckx' `splder` directly; for `N>1` each dimension is differentiated
+ separately, and the results are interleaved in the same way as for [`evaluate`]:
+ `[dx0, dy0, dx1, dy1, ...]` for a two dimensional curve.
+ The values in `x` must be in ascending order; values outside the knot range are clamped.
+ An order of zero returns the spline values, and the order can not exceed the spline degree `K`.
+ */
+pub fn evaluate_derivative<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64], order: usize) -> FitResult<Vec<f64>> {
+    if order > K {
+        return Err(FitError::DerivativeOrder { routine: "splder", order, k: K });
+    }
+    if x.is_empty() {
+        return Ok(Vec::new());
+    }
+    let nc = s.c.len() / N;
+    let mut xy = vec![0.0; x.len() * N];
+    for dim in 0..N {
+        let (ierr, y) = splder::<K>(&s.t, &s.c[dim*nc..(dim+1)*nc], x, order);
+        if ierr>0 {
+            return Err(FitError::from_ier("splder", ierr, s.t.len() as i32, 0.0));
+        }
+        for (i, y) in y.into_iter().enumerate() {
+            xy[i*N + dim] = y;
+        }
+    }
+    Ok(xy)
+}
+
+/**
+ Calculates the definite integral of a spline function from `a` to `b`, wrapping Dierckx' `splint`.
+
+ The spline is considered to be zero outside its knot range, and the limits are clamped to that range
+ before calling `splint`, which does not handle intervals lying completely outside it.
+ If `a > b` the integral changes sign.
+ */
+pub fn integrate<const K: usize>(s: &SplineCurve<K,1>, a: f64, b: f64) -> FitResult<f64> {
+    Ok(integrate_curve(s, a, b)?[0])
+}
+
+/**
+ Calculates the definite integral of each of the dimensions of a spline curve from `a` to `b`.
+
+ See [`integrate`]; the result contains the integral of each of the `N` coordinate functions.
+ */
+pub fn integrate_curve<const K: usize, const N: usize>(s: &SplineCurve<K,N>, a: f64, b: f64) -> FitResult<Vec<f64>> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(FitError::InvalidInput { routine: "splint", reason: format!("integration limits [{a}, {b}] should be finite") });
     }
+    let n = s.t.len();
+    let (tb, te) = (s.t[K], s.t[n-K-1]);
+    let (a, b) = (a.clamp(tb, te), b.clamp(tb, te));
+    let nc = s.c.len() / N;
+    Ok((0..N).map(|dim| splint::<K>(&s.t, &s.c[dim*nc..(dim+1)*nc], a, b)).collect())
+}
+
+/**
+ Finds the parameter values where a cubic spline function crosses `level`, wrapping Dierckx' `sproot`.
+
+ The spline coefficients are shifted by `level`, and the zeros of the shifted spline are returned in
+ ascending order. Only cubic splines (`K=3`) are supported, and the interior knots should be distinct.
+ */
+pub fn roots<const K: usize>(s: &SplineCurve<K,1>, level: f64) -> FitResult<Vec<f64>> {
+    if K != 3 {
+        return Err(FitError::InvalidDegree { routine: "sproot", k: K, allowed: "3" });
+    }
+    let c: Vec<f64> = s.c.iter().map(|c| c - level).collect();
+    let n = s.t.len() as i32;
+    // a cubic polynomial has at most three zeros in each of the n-7 knot intervals
+    let mest = (3 * (n - 7)).max(1);
+    let mut zero = vec![0.0; mest as usize];
+    let mut m = 0;
+    let mut ierr = 0;
+    unsafe {
+        sproot_(
+            s.t.as_ptr(),
+            &n,
+            c.as_ptr(),
+            zero.as_mut_ptr(),
+            &mest,
+            &mut m,
+            &mut ierr
+        );
+    }
+    match ierr {
+        0 => {
+            zero.truncate(m as usize);
+            Ok(zero)
+        },
+        1 => Err(FitError::InsufficientStorage { routine: "sproot", what: "mest", size: mest as usize }),
+        _ => Err(FitError::from_ier("sproot", ierr, n, 0.0)),
+    }
+}
+
+/**
+ Evaluates all derivatives of orders `0..=K` of a spline at a single parameter value `u`,
+ wrapping Dierckx' `spalde` for `N=1`, and `cualde` for `N>1`.
+
+ Element `j` of the result contains the `N` coordinates of the `j`-th derivative; at a knot, right
+ derivatives are returned, except at the end of the knot range, where left derivatives are returned.
+ A parameter value outside the knot range results in an error.
+ */
+pub fn all_derivatives<const K: usize, const N: usize>(s: &SplineCurve<K,N>, u: f64) -> FitResult<Vec<[f64; N]>> {
+    let n = s.t.len();
+    if !(s.t[K]..=s.t[n-K-1]).contains(&u) {
+        return Err(FitError::OutOfRange { routine: if N == 1 { "spalde" } else { "cualde" }, value: u, range: [s.t[K], s.t[n-K-1]] });
+    }
+    let (ierr, d) = match N {
+        1 => spalde(s, u),
+        _ => cualde(s, u),
+    };
+    if ierr>0 {
+        return Err(FitError::from_ier(if N == 1 { "spalde" } else { "cualde" }, ierr, n as i32, 0.0));
+    }
+    Ok(d.chunks(N).map(|dj| {
+        let mut v = [0.0; N];
+        v.copy_from_slice(dj);
+        v
+    }).collect())
 }
 
 fn splev<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64]) -> (i32, Vec<f64>) {
@@ -38,6 +160,93 @@ fn splev<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: &[f64]) -> (i3
     (ierr, y_v)
 }
 
+fn splder<const K: usize>(t: &[f64], c: &[f64], x: &[f64], nu: usize) -> (i32, Vec<f64>) {
+    let k = K as i32;
+    let nu = nu as i32;
+    let m = x.len() as i32;
+    let mut y_v = vec![0.0; m as usize];
+    let n = t.len() as i32;
+    let mut wrk = vec![0.0; n as usize];
+    let mut ierr = 0;
+    unsafe {
+        splder_(
+            t.as_ptr(),
+            &n,
+            c.as_ptr(),
+            &k,
+            &nu,
+            x.as_ptr(),
+            y_v.as_mut_ptr(),
+            &m,
+            wrk.as_mut_ptr(),
+            &mut ierr
+        );
+    }
+    (ierr, y_v)
+}
+
+fn splint<const K: usize>(t: &[f64], c: &[f64], a: f64, b: f64) -> f64 {
+    let k = K as i32;
+    let n = t.len() as i32;
+    let mut wrk = vec![0.0; n as usize];
+    unsafe {
+        splint_(
+            t.as_ptr(),
+            &n,
+            c.as_ptr(),
+            &k,
+            &a,
+            &b,
+            wrk.as_mut_ptr(),
+        )
+    }
+}
+
+fn spalde<const K: usize, const N: usize>(s: &SplineCurve<K,N>, x: f64) -> (i32, Vec<f64>) {
+    let k1 = K as i32 + 1;
+    let n = s.t.len() as i32;
+    let mut d = vec![0.0; K + 1];
+    let mut ierr = 0;
+    unsafe {
+        spalde_(
+            s.t.as_ptr(),
+            &n,
+            s.c.as_ptr(),
+            &k1,
+            &x,
+            d.as_mut_ptr(),
+            &mut ierr
+        );
+    }
+    (ierr, d)
+}
+
+fn cualde<const K: usize, const N: usize>(s: &SplineCurve<K,N>, u: f64) -> (i32, Vec<f64>) {
+    let idim = N as i32;
+    let k1 = K as i32 + 1;
+    let n = s.t.len() as i32;
+    let c = padded_coefficients(s);
+    let nc = c.len() as i32;
+    let nd = k1 * idim;
+    let mut d = vec![0.0; nd as usize];
+    let mut ierr = 0;
+    unsafe {
+        cualde_(
+            &idim,
+            s.t.as_ptr(),
+            &n,
+            c.as_ptr(),
+            &nc,
+            &k1,
+            &u,
+            d.as_mut_ptr(),
+            &nd,
+            &mut ierr
+        );
+    }
+    (ierr, d)
+}
+
 fn curev<const K: usize, const N: usize>(s: &SplineCurve<K,N>, u: &[f64]) -> (i32, Vec<f64>) {
     let k = K as i32;
     let idim = N as i32;
@@ -45,14 +254,15 @@ fn curev<const K: usize, const N: usize>(s: &SplineCurve<K,N>, u: &[f64]) -> (i3
     let mxy = m * idim;
     let mut xy = vec![0.0; mxy as usize];
     let n = s.t.len() as i32;
-    let nc = s.c.len() as i32;
+    let c = padded_coefficients(s);
+    let nc = c.len() as i32;
     let mut ierr = 0;
     unsafe {
         curev_(
             &idim,
             s.t.as_ptr(), 
             &n, 
-            s.c.as_ptr(), 
+            c.as_ptr(), 
             &nc,
             &k, 
             u.as_ptr(), 
@@ -64,3 +274,15 @@ fn curev<const K: usize, const N: usize>(s: &SplineCurve<K,N>, u: &[f64]) -> (i3
     }
     (ierr, xy)
 }
+
+/// Dierckx' curve routines expect the coefficients of each dimension to start at a multiple of the number
+/// of knots `n`, while `SplineCurve` stores them packed, with `n-K-1` values per dimension.
+fn padded_coefficients<const K: usize, const N: usize>(s: &SplineCurve<K,N>) -> Vec<f64> {
+    let n = s.t.len();
+    let nc = s.c.len() / N;
+    let mut c = vec![0.0; n * N];
+    for dim in 0..N {
+        c[dim*n..dim*n+nc].copy_from_slice(&s.c[dim*nc..(dim+1)*nc]);
+    }
+    c
+}
@@ -0,0 +1,272 @@
+//! Errors returned by the fit and evaluation methods of this library.
+//!
+//! Each error records the name of the Dierckx routine which failed, such as `"curfit"` or `"concur"`, together with
+//! the values of the offending parameters, except for invalid spline data, which is not passed to a Dierckx routine.
+
+use std::error;
+use std::fmt;
+
+/// Result type of the fit and evaluation methods of this library.
+pub type FitResult<T> = std::result::Result<T, FitError>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum FitError {
+    /// The storage space reserved for the knots, or other internal data, given by `what`, and with size `size`, is
+    /// too small; for smoothing splines this typically means the smoothing factor `s` is too small (Dierckx `ier=1`).
+    InsufficientStorage { routine: &'static str, what: &'static str, size: usize },
+
+    /// The required tolerance for the smoothing factor `s` can not be reached: `s` is too small (Dierckx `ier=2`).
+    SmoothingTooSmall { routine: &'static str, s: f64 },
+
+    /// The iteration limit for finding a smoothing spline with smoothing factor `s` was reached: `s` is too small
+    /// (Dierckx `ier=3`).
+    IterationLimit { routine: &'static str, s: f64 },
+
+    /// Input values rejected by a Dierckx routine (`ier=10`), or by this library, for the given reason.
+    InvalidInput { routine: &'static str, reason: String },
+
+    /// More derivative constraints were supplied than the maximum allowed for the spline degree.
+    TooManyConstraints { routine: &'static str, max: usize, found: usize },
+
+    /// The constraints can not be satisfied, for the given reason.
+    ConstraintsNotSatisfied { routine: &'static str, reason: &'static str },
+
+    /// The size of the input array `what` does not match the expected size.
+    SizeMismatch { routine: &'static str, what: &'static str, expected: usize, found: usize },
+
+    /// The value at `index` of the input array `what` is not finite.
+    NonFinite { routine: &'static str, what: &'static str, index: usize },
+
+    /// The value at `index` of the input array `what` is not larger than its predecessor, while the values should be in
+    /// strictly ascending order.
+    NotAscending { routine: &'static str, what: &'static str, index: usize },
+
+    /// The weight at `index` is not larger than zero.
+    NonPositiveWeight { routine: &'static str, index: usize, value: f64 },
+
+    /// Not enough data points: at least `min` are required.
+    TooFewDataPoints { routine: &'static str, found: usize, min: usize },
+
+    /// The spline degree `k` is not supported; `allowed` lists the supported values.
+    InvalidDegree { routine: &'static str, k: usize, allowed: &'static str },
+
+    /// The curve dimension `n` should be between 1 and 10.
+    InvalidDimension { routine: &'static str, n: usize },
+
+    /// The knot spacing `dt` of a cardinal spline is too large for the range of the data.
+    KnotSpacing { routine: &'static str, dt: f64 },
+
+    /// The B-spline with coefficient `index`, and support `interval`, has no data point left to determine it: the
+    /// knots do not satisfy the Schoenberg-Whitney conditions.
+    SchoenbergWhitney { routine: &'static str, index: usize, interval: [f64; 2] },
+
+    /// The order of a derivative exceeds the spline degree `k`.
+    DerivativeOrder { routine: &'static str, order: usize, k: usize },
+
+    /// The parameter value `value` is outside the range of the spline.
+    OutOfRange { routine: &'static str, value: f64, range: [f64; 2] },
+
+    /// An optimization did not converge within `iterations` iterations.
+    NotConverged { routine: &'static str, iterations: usize },
+
+    /// The least squares problem is undetermined, as some of the knot intervals, or panels, have too few data
+    /// points: use fewer knots, or a smoothing fit.
+    RankDeficient { routine: &'static str },
+
+    /// An error code of a Dierckx routine without a more specific variant.
+    Dierckx { routine: &'static str, ier: i32 },
+
+    /// Spline data, such as read by `OwnedSplineCurveData`, which does not describe a valid spline, for the given
+    /// reason.
+    InvalidSplineData { reason: String },
+}
+
+impl FitError {
+    /// Error for a Dierckx fit routine error flag `ier > 0`, with `nest` the size of the knot array, and `s` the
+    /// smoothing factor used.
+    pub(crate) fn from_ier(routine: &'static str, ier: i32, nest: i32, s: f64) -> Self {
+        match ier {
+            1 => Self::InsufficientStorage { routine, what: "nest", size: nest as usize },
+            2 => Self::SmoothingTooSmall { routine, s },
+            3 => Self::IterationLimit { routine, s },
+            10 => Self::InvalidInput { routine, reason: Self::input_conditions(routine).to_string() },
+            _ => Self::Dierckx { routine, ier },
+        }
+    }
+
+    /// Input conditions checked by a Dierckx routine, reported when it returns `ier=10`.
+    fn input_conditions(routine: &str) -> &'static str {
+        match routine {
+            "curfit" => "check if -1<=iopt<=1, 1<=k<=5, m>k, nest>2*k+2, w(i)>0, xb<=x(1)<x(2)<...<x(m)<=xe, \
+                lwrk>=(k+1)*m+nest*(7+3*k)",
+            "concur" => "check if -1<=iopt<=1, k=1,3 or 5, m>k-max(0,ib-1)-max(0,ie-1), nest>2*k+2, 0<idim<=10, \
+                w(i)>0, ub<=u(1)<u(2)<...<u(m)<=ue, 0<=ib<=(k+1)/2, 0<=ie<=(k+1)/2, nc>=nest*idim, \
+                lwrk>=(k+1)*m+nest*(6+idim+3*k)",
+            "clocur" => "check if -1<=iopt<=1, 1<=k<=5, m>1, nest>2*k+2, 0<idim<=10, w(i)>0, u(1)<u(2)<...<u(m), \
+                first and last data point equal, nc>=nest*idim, lwrk>=m*(k+1)+nest*(7+idim+5*k)",
+            "parcur" => "check if -1<=iopt<=1, 1<=k<=5, m>k, nest>2*k+2, 0<idim<=10, w(i)>0, \
+                ub<=u(1)<u(2)<...<u(m)<=ue, nc>=nest*idim, lwrk>=m*(k+1)+nest*(6+idim+3*k)",
+            "concon" => "check if 0<=iopt<=1, m>3, nest>=8, w(i)>0, x(1)<x(2)<...<x(m), maxtr>=1, maxbin>=1, \
+                lwrk>=m*4+nest*8+maxbin*(maxbin+nest+1), kwrk>=maxtr*4+2*(maxbin+1)",
+            _ => "check the knots, the number of coefficients, and the spline degree",
+        }
+    }
+
+    /// Name of the Dierckx routine which failed, if any.
+    pub fn routine(&self) -> Option<&'static str> {
+        match self {
+            Self::InsufficientStorage { routine, .. }
+            | Self::SmoothingTooSmall { routine, .. }
+            | Self::IterationLimit { routine, .. }
+            | Self::InvalidInput { routine, .. }
+            | Self::TooManyConstraints { routine, .. }
+            | Self::ConstraintsNotSatisfied { routine, .. }
+            | Self::SizeMismatch { routine, .. }
+            | Self::NonFinite { routine, .. }
+            | Self::NotAscending { routine, .. }
+            | Self::NonPositiveWeight { routine, .. }
+            | Self::TooFewDataPoints { routine, .. }
+            | Self::InvalidDegree { routine, .. }
+            | Self::InvalidDimension { routine, .. }
+            | Self::KnotSpacing { routine, .. }
+            | Self::SchoenbergWhitney { routine, .. }
+            | Self::DerivativeOrder { routine, .. }
+            | Self::OutOfRange { routine, .. }
+            | Self::NotConverged { routine, .. }
+            | Self::RankDeficient { routine }
+            | Self::Dierckx { routine, .. } => Some(routine),
+            Self::InvalidSplineData { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(routine) = self.routine() {
+            write!(f, "{routine}: ")?;
+        }
+        match self {
+            Self::InsufficientStorage { what, size, .. } => {
+                write!(f, "out of storage space ({what}={size}); for smoothing splines, s may be too small")
+            }
+            Self::SmoothingTooSmall { s, .. } => write!(f, "smoothing spline error, s={s} too small"),
+            Self::IterationLimit { s, .. } => {
+                write!(f, "reached iteration limit for finding smoothing spline; s={s} too small")
+            }
+            Self::InvalidInput { reason, .. } => write!(f, "invalid input data; {reason}"),
+            Self::TooManyConstraints { max, found, .. } => {
+                write!(f, "too many derivative constraints supplied: {found}, the maximum is {max}")
+            }
+            Self::ConstraintsNotSatisfied { reason, .. } => write!(f, "constraints can not be satisfied: {reason}"),
+            Self::SizeMismatch { what, expected, found, .. } => {
+                write!(f, "wrong size of array {what}: expected {expected}, found {found}")
+            }
+            Self::NonFinite { what, index, .. } => write!(f, "value {what}[{index}] is not finite"),
+            Self::NotAscending { what, index, .. } => {
+                write!(f, "{what}[{index}] is not larger than {what}[{}]: values should be strictly ascending", index - 1)
+            }
+            Self::NonPositiveWeight { index, value, .. } => {
+                write!(f, "weight w[{index}]={value} should be larger than zero")
+            }
+            Self::TooFewDataPoints { found, min, .. } => {
+                write!(f, "need at least {min} data points, found {found}")
+            }
+            Self::InvalidDegree { k, allowed, .. } => write!(f, "spline degree {k} not supported, use {allowed}"),
+            Self::InvalidDimension { n, .. } => write!(f, "dimension N={n} should be between 1 and 10"),
+            Self::KnotSpacing { dt, .. } => {
+                write!(f, "cardinal spline knot spacing {dt} too large: select smaller interval")
+            }
+            Self::SchoenbergWhitney { index, interval, .. } => write!(
+                f,
+                "no data point left for B-spline {index}, with support [{}, {}]: the knots do not satisfy the \
+                Schoenberg-Whitney conditions",
+                interval[0], interval[1]
+            ),
+            Self::DerivativeOrder { order, k, .. } => {
+                write!(f, "derivative order {order} should not exceed the spline degree {k}")
+            }
+            Self::OutOfRange { value, range, .. } => {
+                write!(f, "value {value} outside the range [{}, {}] of the spline", range[0], range[1])
+            }
+            Self::NotConverged { iterations, .. } => write!(f, "not converged in {iterations} iterations"),
+            Self::RankDeficient { .. } => write!(
+                f,
+                "too few data points in the knot intervals to determine the spline: use fewer knots, or a smoothing fit"
+            ),
+            Self::Dierckx { ier, .. } => write!(f, "error flag ier={ier}"),
+            Self::InvalidSplineData { reason } => write!(f, "invalid spline data; {reason}"),
+        }
+    }
+}
+
+impl error::Error for FitError {}
+
+/// Result type of the CSV reader and writer.
+pub type CsvResult<T> = std::result::Result<T, CsvError>;
+
+/// Errors returned by the CSV reader and writer of the [`io`](crate::io) module.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CsvError {
+    /// An error reported by the `csv` crate, such as an I/O error, or a malformed record.
+    Csv(csv::Error),
+
+    /// A column selected by `name` is not in the header, or the file is read without header.
+    UnknownColumn { name: String },
+
+    /// The value in `column`, with a zero based index, at `line` of the file, is not a number.
+    Parse { line: u64, column: usize, value: String },
+
+    /// The value in `column`, with a zero based index, at `line` of the file, is empty or absent.
+    Missing { line: u64, column: usize },
+
+    /// The columns to write do not all have the same number of values as the first column.
+    ColumnLength { column: usize, expected: usize, found: usize },
+
+    /// The number of column names set differs from the number of columns to write.
+    Headers { expected: usize, found: usize },
+
+    /// The spline, or its derivatives, could not be evaluated at the values to write.
+    Fit(FitError),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Csv(e) => write!(f, "csv: {e}"),
+            Self::UnknownColumn { name } => write!(f, "csv: column \"{name}\" not found in the header"),
+            Self::Parse { line, column, value } => {
+                write!(f, "csv: line {line}, column {column}: \"{value}\" is not a number")
+            }
+            Self::Missing { line, column } => write!(f, "csv: line {line}, column {column}: missing value"),
+            Self::ColumnLength { column, expected, found } => {
+                write!(f, "csv: column {column} has {found} values, expected {expected}")
+            }
+            Self::Headers { expected, found } => write!(f, "csv: {found} column names for {expected} columns"),
+            Self::Fit(e) => write!(f, "csv: {e}"),
+        }
+    }
+}
+
+impl error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Csv(e) => Some(e),
+            Self::Fit(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(e: csv::Error) -> Self {
+        Self::Csv(e)
+    }
+}
+
+impl From<FitError> for CsvError {
+    fn from(e: FitError) -> Self {
+        Self::Fit(e)
+    }
+}
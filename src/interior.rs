@@ -0,0 +1,193 @@
+//! Interior Point and Derivative Constraints
+//!
+//! Dierckx' `concur` only constrains the derivatives at the end points of a curve. The interior constraints of
+//! `ParameterSplineCurveFit`, set with its `interior_constraints` method, prescribe the value, or a derivative of any
+//! order up to `K`, of all `N` coordinates of the curve at given parameter values, for example way points which the
+//! curve should pass through, with given tangents.
+//!
+//! The constraints are applied on the knots calculated by `concur`: with these knots, the coefficients `c` are
+//! fitted again, by weighted least squares with the equality constraints `C c = v`. The end point constraints are
+//! included, so they remain satisfied. For smoothing fits, the sum of the squared jumps of the `K`-th derivative at the
+//! knots, which the `concur` smoothing spline minimizes, is added with a penalty weight, such that the sum of squared
+//! residuals is equal to that of the `concur` fit, as for the smoothing splines scored in the [`select`](crate::select)
+//! module. If the constraints do not allow a fit that close to the data, the constrained least squares fit is used.
+//!
+//! With `c0` the solution of the least squares system without the constraints, with triangular matrix `R`, and
+//! `Z = (R'R)⁻¹ C'`, the solution is `c = c0 - Z (C Z)⁻¹ (C c0 - v)`, calculated for all coordinates at once.
+//! If `R` is singular, which is the case if the spline has more coefficients than there are data points, as for
+//! interpolating curves with end point constraints, rows with a small weight are added, pulling the coefficients
+//! towards those calculated by `concur`: of the fits with the same residuals, the one closest to the `concur` fit is
+//! used. The constraints should be linearly independent, and each constraint needs its own B-spline coefficient; more
+//! knots, for example from a smaller rms value, can be required to satisfy all of them.
+
+use crate::bspline::{basis, basis_derivatives, discontinuity_jumps, interval, MAX_ORDER};
+use crate::lsq::{smoothing_weight, LeastSquares};
+use crate::{FitError, FitResult};
+use spliny::SplineCurve;
+
+// relative weight of the ridge rows, used if the least squares system is singular
+const RIDGE: f64 = 1e-6;
+
+/// An interior constraint: the derivative of order `.1` of the curve at parameter value `.0` should be equal to `.2`,
+/// with order 0 for the position of the curve.
+pub type InteriorConstraint<const N: usize> = (f64, usize, [f64; N]);
+
+/// Checks the constraints: parameter values in the range `[ub, ue]`, derivative orders up to `k`, and finite values.
+pub(crate) fn check<const N: usize>(
+    routine: &'static str,
+    constraints: &[InteriorConstraint<N>],
+    k: usize,
+    [ub, ue]: [f64; 2],
+) -> FitResult<()> {
+    for (index, &(u, order, value)) in constraints.iter().enumerate() {
+        if !(ub..=ue).contains(&u) {
+            return Err(FitError::OutOfRange { routine, value: u, range: [ub, ue] });
+        }
+        if order > k {
+            return Err(FitError::DerivativeOrder { routine, order, k });
+        }
+        if value.iter().any(|v| !v.is_finite()) {
+            return Err(FitError::NonFinite { routine, what: "constraint values", index });
+        }
+    }
+    Ok(())
+}
+
+/// Refits the coefficients of spline `s`, fitted to data with parameter values `u`, coordinates `xn` and weights `w`,
+/// such that it satisfies the constraints. For a smoothing spline, `fp` is its sum of squared residuals, which is
+/// kept if the constraints allow.
+pub(crate) fn constrain<const K: usize, const N: usize>(
+    routine: &'static str,
+    s: &mut SplineCurve<K, N>,
+    (u, xn, w): (&[f64], &[f64], &[f64]),
+    constraints: &[InteriorConstraint<N>],
+    fp: Option<f64>,
+) -> FitResult<()> {
+    let t = &s.t;
+    let nb = t.len() - K - 1;
+    let p = constraints.len();
+    if p == 0 {
+        return Ok(());
+    }
+    if p > nb {
+        return Err(FitError::TooManyConstraints { routine, max: nb, found: p });
+    }
+    // observation rows, and constraint rows, as the index of their first non-zero coefficient and the coefficients
+    let data: Vec<(usize, [f64; MAX_ORDER])> = u
+        .iter()
+        .map(|&u| {
+            let l = interval(t, K, u);
+            (l - K, basis(t, K, u, l))
+        })
+        .collect();
+    let rows: Vec<(usize, [f64; MAX_ORDER])> = constraints
+        .iter()
+        .map(|&(u, order, _)| {
+            let l = interval(t, K, u);
+            (l - K, basis_derivatives(t, K, u, l, order))
+        })
+        .collect();
+    let jumps = discontinuity_jumps(t, K);
+    let c0 = &s.c;
+
+    // least squares system, with penalty rows of weight `lambda`, and ridge rows of weight `ridge` if not zero
+    let system = |lambda: f64, ridge: f64| {
+        let mut lsq = LeastSquares::with_rhs(nb, N, K + 2, 0);
+        for ((j, b), (x, &w)) in data.iter().zip(xn.chunks(N).zip(w)) {
+            lsq.add_row_rhs((0..=K).map(|a| (j + a, b[a])), x, w);
+        }
+        if lambda > 0.0 {
+            for (j, jump) in &jumps {
+                lsq.add_row_rhs((0..K + 2).map(|a| (j + a, jump[a])), &[0.0; N], lambda);
+            }
+        }
+        if ridge > 0.0 {
+            for j in 0..nb {
+                let z: [f64; N] = std::array::from_fn(|d| c0[d * nb + j]);
+                lsq.add_row_rhs([(j, 1.0)], &z, ridge);
+            }
+        }
+        lsq
+    };
+    let dot = |(j, a): &(usize, [f64; MAX_ORDER]), v: &dyn Fn(usize) -> f64| -> f64 {
+        (0..=K).map(|i| a[i] * v(j + i)).sum()
+    };
+    let wmax = w.iter().fold(0.0, |m: f64, &w| m.max(w));
+
+    // constrained fit, with its coefficients, arranged as `c[d*nb+j]`, and its sum of squared residuals
+    let fit = |lambda: f64| -> FitResult<(Vec<f64>, f64)> {
+        let (x, z) = [0.0, RIDGE * wmax]
+            .iter()
+            .find_map(|&ridge| {
+                let lsq = system(lambda, ridge);
+                let z: Option<Vec<Vec<f64>>> =
+                    rows.iter().map(|(j, a)| lsq.solve_normal((0..=K).map(|i| (j + i, a[i])))).collect();
+                Some((lsq.solve()?, z?))
+            })
+            .ok_or(FitError::RankDeficient { routine })?;
+
+        // C Z, and the constraint violations C c0 - v, for all dimensions
+        let mut g: Vec<f64> = rows.iter().flat_map(|r| z.iter().map(|z| dot(r, &|j| z[j]))).collect();
+        let mut rhs: Vec<f64> = rows
+            .iter()
+            .zip(constraints)
+            .flat_map(|(r, (_, _, v))| (0..N).map(|d| dot(r, &|j| x[j * N + d]) - v[d]).collect::<Vec<_>>())
+            .collect();
+        let multipliers = gauss(&mut g, &mut rhs, p, N).ok_or(FitError::ConstraintsNotSatisfied {
+            routine,
+            reason: "the constraints are linearly dependent, or there are too few knots near the constraints",
+        })?;
+        let c: Vec<f64> = (0..N)
+            .flat_map(|d| (0..nb).map(move |j| (d, j)))
+            .map(|(d, j)| x[j * N + d] - z.iter().enumerate().map(|(q, z)| z[j] * multipliers[q * N + d]).sum::<f64>())
+            .collect();
+        let fp = data
+            .iter()
+            .zip(xn.chunks(N).zip(w))
+            .map(|(r, (x, w))| (0..N).map(|d| w * w * (x[d] - dot(r, &|j| c[d * nb + j])).powi(2)).sum::<f64>())
+            .sum();
+        Ok((c, fp))
+    };
+    let lsq = fit(0.0)?;
+    s.c = match fp {
+        Some(fp) if lsq.1 < fp => smoothing_weight(lsq, fp, fit)?.0,
+        _ => lsq.0,
+    };
+    Ok(())
+}
+
+// solves the dense system `a x = b`, with `a` a `p x p` matrix and `b` with `nrhs` right hand sides, both row major,
+// by Gaussian elimination with partial pivoting; returns `None` if `a` is singular
+fn gauss(a: &mut [f64], b: &mut [f64], p: usize, nrhs: usize) -> Option<Vec<f64>> {
+    let amax = a.iter().fold(0.0, |m: f64, v| m.max(v.abs()));
+    for col in 0..p {
+        let piv = (col..p).max_by(|&i, &j| a[i * p + col].abs().total_cmp(&a[j * p + col].abs()))?;
+        if a[piv * p + col].abs() <= amax * 1e-12 {
+            return None;
+        }
+        for j in 0..p {
+            a.swap(col * p + j, piv * p + j);
+        }
+        for r in 0..nrhs {
+            b.swap(col * nrhs + r, piv * nrhs + r);
+        }
+        for i in col + 1..p {
+            let f = a[i * p + col] / a[col * p + col];
+            for j in col..p {
+                a[i * p + j] -= f * a[col * p + j];
+            }
+            for r in 0..nrhs {
+                b[i * nrhs + r] -= f * b[col * nrhs + r];
+            }
+        }
+    }
+    let mut x = vec![0.0; p * nrhs];
+    for i in (0..p).rev() {
+        for r in 0..nrhs {
+            let s: f64 = (i + 1..p).map(|j| a[i * p + j] * x[j * nrhs + r]).sum();
+            x[i * nrhs + r] = (b[i * nrhs + r] - s) / a[i * p + i];
+        }
+    }
+    Some(x)
+}
+
@@ -0,0 +1,340 @@
+//! Reading and Writing Data in CSV Files
+//!
+//! [`CsvReader`] reads columns of numbers from CSV files, or any other source implementing `std::io::Read`, with
+//! columns selected by their zero based index, or by their name in the header. It returns the data in the form used
+//! by the fitters: x and y values, optionally with weights, for `SplineCurveFit`, and parameter values and
+//! `N`-dimensional coordinates for `ParameterSplineCurveFit`.
+//!
+//! Values are trimmed before they are parsed, and lines starting with `#` are skipped, by default. Empty and absent
+//! values result in an error, or the rows containing them are skipped, if `skip_missing` is set. All errors report
+//! the line in the file, and the column, of the offending value.
+//!
+//! [`CsvWriter`] writes columns of numbers, with optional header names, a fixed number of decimals, and a configurable
+//! delimiter. Its `write_spline` method samples a `SplineCurve<K,N>`, and optionally its derivatives, at a given set
+//! of parameter values, and writes these with the parameter values in the first column.
+
+use crate::{dierckx_val, CsvError, CsvResult};
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use spliny::SplineCurve;
+use std::fs::File;
+use std::path::Path;
+
+/// Column of a CSV file, selected by its zero based index, or by its name in the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(i: usize) -> Self {
+        Self::Index(i)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+/// Reader for columns of numbers from CSV files.
+#[derive(Debug, Clone)]
+pub struct CsvReader {
+    has_headers: bool,
+    delimiter: u8,
+    comment: Option<u8>,
+    trim: bool,
+    skip_missing: bool,
+}
+
+impl Default for CsvReader {
+    fn default() -> Self {
+        Self { has_headers: true, delimiter: b',', comment: Some(b'#'), trim: true, skip_missing: false }
+    }
+}
+
+impl CsvReader {
+    /// Reader for comma separated files with a header, trimming values, and skipping lines starting with `#`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets if the first line is a header with the column names, which is the default.
+    pub fn has_headers(mut self, yes: bool) -> Self {
+        self.has_headers = yes;
+        self
+    }
+
+    /// Sets the field delimiter, `b','` by default.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the character marking comment lines, `Some(b'#')` by default, or `None` for no comment lines.
+    pub fn comment(mut self, comment: Option<u8>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Sets if leading and trailing whitespace is removed from the values before they are parsed, which is the
+    /// default.
+    pub fn trim(mut self, yes: bool) -> Self {
+        self.trim = yes;
+        self
+    }
+
+    /// Skips the rows with an empty or absent value in one of the selected columns, instead of returning an error.
+    pub fn skip_missing(mut self, yes: bool) -> Self {
+        self.skip_missing = yes;
+        self
+    }
+
+    /// Reads the selected columns from a file.
+    pub fn read(&self, path: impl AsRef<Path>, columns: &[Column]) -> CsvResult<Vec<Vec<f64>>> {
+        let file = File::open(path).map_err(csv::Error::from)?;
+        self.read_from(file, columns)
+    }
+
+    /// Reads the selected columns from a reader.
+    pub fn read_from(&self, mut rdr: impl std::io::Read, columns: &[Column]) -> CsvResult<Vec<Vec<f64>>> {
+        // the line numbers counted by the csv crate skip comment and empty lines, so they are counted here, from the
+        // byte positions of the records
+        let mut input = Vec::new();
+        rdr.read_to_end(&mut input).map_err(csv::Error::from)?;
+        let (mut pos, mut line) = (0, 1);
+        let comment = self.comment;
+        let mut line_at = |byte: usize| {
+            // the position of a record includes the comment and empty lines before it
+            let mut byte = byte.max(pos);
+            while let Some(b) = input.get(byte).copied().filter(|&b| b == b'\r' || b == b'\n' || Some(b) == comment) {
+                byte += match b {
+                    b'\r' | b'\n' => 1,
+                    _ => input[byte..].iter().position(|&b| b == b'\n').map_or(input.len() - byte, |n| n + 1),
+                };
+            }
+            line += input[pos..byte].iter().filter(|&&b| b == b'\n').count() as u64;
+            pos = byte;
+            line
+        };
+
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(self.has_headers)
+            .delimiter(self.delimiter)
+            .comment(self.comment)
+            .trim(if self.trim { Trim::All } else { Trim::None })
+            .flexible(true)
+            .from_reader(&input[..]);
+        let header = if self.has_headers { Some(rdr.headers()?.clone()) } else { None };
+        let index: Vec<usize> = columns.iter().map(|c| column_index(c, header.as_ref())).collect::<CsvResult<_>>()?;
+
+        let mut data = vec![Vec::new(); columns.len()];
+        let mut values = vec![0.0; columns.len()];
+        'rows: for record in rdr.records() {
+            let record = record?;
+            let line = line_at(record.position().map_or(0, |p| p.byte() as usize));
+            for (v, &column) in values.iter_mut().zip(&index) {
+                match record.get(column).filter(|s| !s.is_empty()) {
+                    Some(s) => {
+                        *v = s.parse().map_err(|_| CsvError::Parse { line, column, value: s.to_string() })?
+                    }
+                    None if self.skip_missing => continue 'rows,
+                    None => return Err(CsvError::Missing { line, column }),
+                }
+            }
+            data.iter_mut().zip(&values).for_each(|(d, &v)| d.push(v));
+        }
+        Ok(data)
+    }
+
+    /// Reads x and y values, for `SplineCurveFit`.
+    pub fn read_xy(
+        &self,
+        path: impl AsRef<Path>,
+        x: impl Into<Column>,
+        y: impl Into<Column>,
+    ) -> CsvResult<(Vec<f64>, Vec<f64>)> {
+        let mut data = self.read(path, &[x.into(), y.into()])?.into_iter();
+        Ok((data.next().unwrap(), data.next().unwrap()))
+    }
+
+    /// Reads x and y values, and weights, for `SplineCurveFit` and its `set_weights` method.
+    pub fn read_xyw(
+        &self,
+        path: impl AsRef<Path>,
+        x: impl Into<Column>,
+        y: impl Into<Column>,
+        w: impl Into<Column>,
+    ) -> CsvResult<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+        let mut data = self.read(path, &[x.into(), y.into(), w.into()])?.into_iter();
+        Ok((data.next().unwrap(), data.next().unwrap(), data.next().unwrap()))
+    }
+
+    /// Reads curve parameter values `u`, and `N`-dimensional coordinates `xn`, arranged as
+    /// `[x0, y0, z0, x1, y1, ...]`, for `ParameterSplineCurveFit<K,N>`.
+    pub fn read_parametric<const N: usize>(
+        &self,
+        path: impl AsRef<Path>,
+        u: impl Into<Column>,
+        xn: [Column; N],
+    ) -> CsvResult<(Vec<f64>, Vec<f64>)> {
+        let columns: Vec<Column> = std::iter::once(u.into()).chain(xn).collect();
+        let mut data = self.read(path, &columns)?;
+        let xn = (0..data[0].len()).flat_map(|i| data[1..].iter().map(move |d| d[i])).collect();
+        Ok((std::mem::take(&mut data[0]), xn))
+    }
+}
+
+fn column_index(column: &Column, header: Option<&StringRecord>) -> CsvResult<usize> {
+    match column {
+        Column::Index(i) => Ok(*i),
+        Column::Name(name) => header
+            .and_then(|h| h.iter().position(|h| h == name))
+            .ok_or_else(|| CsvError::UnknownColumn { name: name.clone() }),
+    }
+}
+
+/// Writer for columns of numbers to CSV files.
+#[derive(Debug, Clone)]
+pub struct CsvWriter {
+    headers: Option<Vec<String>>,
+    precision: Vec<usize>,
+    delimiter: u8,
+}
+
+impl Default for CsvWriter {
+    fn default() -> Self {
+        Self { headers: None, precision: Vec::new(), delimiter: b',' }
+    }
+}
+
+impl CsvWriter {
+    /**
+     Writer for comma separated files, without header, and with the values formatted with the least number of
+     decimals needed to read them back unchanged.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the column names, written as the first line of the file, one for each column.
+    pub fn headers<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.headers = Some(names.into_iter().map(|s| s.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Sets the number of decimals of all columns.
+    pub fn precision(mut self, decimals: usize) -> Self {
+        self.precision = vec![decimals];
+        self
+    }
+
+    /// Sets the number of decimals for each column; the last value applies to any further columns.
+    pub fn precisions(mut self, decimals: Vec<usize>) -> Self {
+        self.precision = decimals;
+        self
+    }
+
+    /// Sets the field delimiter, `b','` by default.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Writes the columns to a file, with a row for each value; all columns should have the same length.
+    pub fn write(&self, path: impl AsRef<Path>, columns: &[&[f64]]) -> CsvResult<()> {
+        let file = File::create(path).map_err(csv::Error::from)?;
+        self.write_to(file, columns)
+    }
+
+    /// Writes the columns to a writer.
+    pub fn write_to(&self, wtr: impl std::io::Write, columns: &[&[f64]]) -> CsvResult<()> {
+        let n = columns.first().map_or(0, |c| c.len());
+        if let Some((column, c)) = columns.iter().enumerate().find(|(_, c)| c.len() != n) {
+            return Err(CsvError::ColumnLength { column, expected: n, found: c.len() });
+        }
+        let mut wtr = WriterBuilder::new().delimiter(self.delimiter).from_writer(wtr);
+        if let Some(headers) = &self.headers {
+            if headers.len() != columns.len() {
+                return Err(CsvError::Headers { expected: columns.len(), found: headers.len() });
+            }
+            wtr.write_record(headers)?;
+        }
+        let mut record = vec![String::new(); columns.len()];
+        for i in 0..n {
+            for (j, (r, c)) in record.iter_mut().zip(columns).enumerate() {
+                *r = match self.precision.get(j).or(self.precision.last()) {
+                    Some(&p) => format!("{:.p$}", c[i]),
+                    None => c[i].to_string(),
+                };
+            }
+            wtr.write_record(&record)?;
+        }
+        wtr.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+
+    /**
+     * Writes the values of a spline, at the parameter values `u`, to a file.
+     *
+     * The first column contains the parameter values, followed by the `N` coordinates of the spline, and, for
+     * `derivatives` larger than zero, the coordinates of its derivatives up to that order. Without headers set, the
+     * columns are named `u`, `x0`, `x1`, ... and `d1x0`, `d1x1`, ... for the first derivatives, or `x`, `y`, `d1y`,
+     * `d2y`, ... for `N=1`.
+     *
+     * Arguments:
+     * - path: output file
+     * - s: spline to sample
+     * - u: parameter values, in strictly ascending order
+     * - derivatives: highest derivative order to write, at most the spline degree `K`
+     */
+    pub fn write_spline<const K: usize, const N: usize>(
+        &self,
+        path: impl AsRef<Path>,
+        s: &SplineCurve<K, N>,
+        u: &[f64],
+        derivatives: usize,
+    ) -> CsvResult<()> {
+        let file = File::create(path).map_err(csv::Error::from)?;
+        self.write_spline_to(file, s, u, derivatives)
+    }
+
+    /// Writes the values of a spline, at the parameter values `u`, to a writer; see [`CsvWriter::write_spline`].
+    pub fn write_spline_to<const K: usize, const N: usize>(
+        &self,
+        wtr: impl std::io::Write,
+        s: &SplineCurve<K, N>,
+        u: &[f64],
+        derivatives: usize,
+    ) -> CsvResult<()> {
+        let mut values = vec![dierckx_val::evaluate(s, u)?];
+        for order in 1..=derivatives {
+            values.push(dierckx_val::evaluate_derivative(s, u, order)?);
+        }
+        // values are interleaved by coordinate, columns are written per coordinate
+        let coordinates: Vec<Vec<f64>> = values
+            .iter()
+            .flat_map(|v| (0..N).map(move |d| v.iter().skip(d).step_by(N).copied().collect()))
+            .collect();
+        let columns: Vec<&[f64]> = std::iter::once(u).chain(coordinates.iter().map(Vec::as_slice)).collect();
+
+        let writer = match self.headers {
+            Some(_) => self.clone(),
+            None => self.clone().headers(spline_headers(N, derivatives)),
+        };
+        writer.write_to(wtr, &columns)
+    }
+}
+
+// default column names for a sampled spline with n coordinates, and its derivatives
+fn spline_headers(n: usize, derivatives: usize) -> Vec<String> {
+    let name = |order: usize, d: usize| {
+        let x = if n == 1 { "y".to_string() } else { format!("x{d}") };
+        if order == 0 { x } else { format!("d{order}{x}") }
+    };
+    let u = if n == 1 { "x" } else { "u" };
+    std::iter::once(u.to_string())
+        .chain((0..=derivatives).flat_map(|order| (0..n).map(move |d| name(order, d))))
+        .collect()
+}
@@ -2,7 +2,7 @@
 
 
 /// Foreign Function Interface definitions
-//mod dierckx;
+mod dierckx;
 
 pub mod curfit;
 pub use curfit::*;
@@ -13,24 +13,73 @@ pub use concur::*;
 pub mod clocur;
 pub use clocur::*;
 
+pub mod percur;
+pub use percur::*;
+
+pub mod parcur;
+pub use parcur::*;
+
+pub mod concon;
+pub use concon::*;
+
+pub mod multi;
+pub use multi::*;
+
+pub mod select;
+pub use select::*;
+
+pub mod report;
+pub use report::*;
+
+pub mod robust;
+pub use robust::*;
+
+pub mod monotone;
+pub use monotone::*;
+
+pub mod interior;
+pub use interior::*;
+
+pub mod workspace;
+pub use workspace::*;
+
+#[cfg(feature = "rayon")]
+pub mod batch;
+#[cfg(feature = "rayon")]
+pub use batch::*;
+
 pub mod dierckx_val;
 
+/// B-spline basis functions and least squares solver, for the methods implemented in Rust
+mod bspline;
+mod lsq;
+
+/// Input checks, run before data is passed to the Dierckx routines
+mod validate;
+
+pub mod io;
+pub use io::*;
+
 pub mod util;
 pub use util::*;
 
-use std::error;
-use std::fmt;
-//use crate::dierckx::{splev_};
-//use dierckx_sys::{splev_};
+pub mod error;
+pub use error::*;
 
+use std::error::Error;
 
-pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+/// General result type, used by the utility functions, with `FitError` and other errors boxed.
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 // Single Output Spline Fit
 pub type LinearSplineFit = SplineCurveFit<1>;
 pub type CubicSplineFit = SplineCurveFit<3>;
 pub type QuinticSplineFit = SplineCurveFit<5>;
 
+// Single Output Periodic Spline Fit
+pub type LinearPeriodicSplineFit = PeriodicSplineCurveFit<1>;
+pub type CubicPeriodicSplineFit = PeriodicSplineCurveFit<3>;
+pub type QuinticPeriodicSplineFit = PeriodicSplineCurveFit<5>;
 
 // Multi-Output Parametrized Curve Fits
 pub type LinearSplineFit1D = ParameterSplineCurveFit<1,1>;
@@ -43,6 +92,11 @@ pub type LinearSplineFit3D = ParameterSplineCurveFit<1,3>;
 pub type CubicSplineFit3D = ParameterSplineCurveFit<3,3>;
 pub type QuinticSplineFit3D = ParameterSplineCurveFit<5,3>;
 
+// Multiple Series Fits, with Shared Knots
+pub type LinearMultiSeriesFit<const M: usize> = MultiSeriesFit<1,M>;
+pub type CubicMultiSeriesFit<const M: usize> = MultiSeriesFit<3,M>;
+pub type QuinticMultiSeriesFit<const M: usize> = MultiSeriesFit<5,M>;
+
 // Closed Periodic Curve Fits
 pub type ClosedLinearSplineFit2D = ClosedParameterSplineCurveFit<1,2>;
 pub type ClosedCubicSplineFit2D = ClosedParameterSplineCurveFit<3,2>;
@@ -50,40 +104,3 @@ pub type ClosedQuinticSplineFit2D = ClosedParameterSplineCurveFit<5,2>;
 pub type ClosedLinearSplineFit3D = ClosedParameterSplineCurveFit<1,3>;
 pub type ClosedCubicSplineFit3D = ClosedParameterSplineCurveFit<3,3>;
 pub type ClosedQuinticSplineFit3D = ClosedParameterSplineCurveFit<5,3>;
-
-#[derive(Debug, Clone)]
-pub struct FitError(i32);
-
-impl FitError {
-    fn new(ierr: i32) -> Self { Self(ierr) }
-}
-
-impl fmt::Display for FitError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.0 {
-            // Dierckx
-           -2 => write!(f, "normal return for weighted least squares spline, fp upper bound for smoothing factor"),
-           -1 => write!(f, "normal return for interpolating spline"),
-            0 => write!(f, "normal return"),
-            1 => write!(f, "out of storage space; nest too small (m/2); or s too small"),
-            2 => write!(f, "smoothing spline error, s too small"),
-            3 => write!(f, "reached iteration limit (20) for finding smoothing spline; s too small"),
-           10 => write!(f, "invalid input data; check if -1<=iopt<=1, 1<=k<=5, m>k, nest>2*k+2, w(i)>0,i=1,2,...,m xb<=x(1)<x(2)<...<x(m)<=xe, lwrk>=(k+1)*m+nest*(7+3*k)"),
-           // this library
-           200 => write!(f, "N should be between 1 and 10"),
-           201 => write!(f, "need at least 2 parameter values"),
-           202 => write!(f, "incorrect size of coordinate array xn"),
-           203 => write!(f, "wrong size for weights array"),
-           204 => write!(f, "too many derivative contraints supplied"),
-           205 => write!(f, "cardinal spline spacing too large: select smaller interval"),
-           206 => write!(f, "smoothing_spline not converged"),
-           207 => write!(f, "failed to initialize smoothing_spline"),
-           208 => write!(f, "K should be 1, 3 or 5"),
-            _ => write!(f, "unknown error"),
-        }
-    }
-}
-
-
-impl error::Error for FitError {}
-
@@ -0,0 +1,220 @@
+//! Linear least squares solver, using Givens rotations, for the fitting methods implemented in Rust.
+//!
+//! Observation rows are added one at a time, and rotated into an upper triangular matrix `R`, as done by
+//! Dierckx' `fpgivs` and `fprota` routines. As in `fpcurf`, `R` is stored in banded form, with the `nb` elements of
+//! each row starting at the diagonal, which is enough for observation rows with their non-zero coefficients in `nb`
+//! consecutive columns. Rows of periodic problems also have coefficients in the last columns, which are stored in full
+//! for each row of `R`, as in `fpperi`. Memory use is proportional to the number of unknowns, and the cost of a row to
+//! the square of the bandwidth.
+
+use std::iter::Chain;
+use std::ops::Range;
+
+// relative tolerance for the sum of squared residuals of a smoothing spline, as used by Dierckx
+const TOL: f64 = 0.001;
+// maximum number of iterations to find the smoothing weight
+const MAXIT: usize = 60;
+
+#[derive(Clone)]
+pub(crate) struct LeastSquares {
+    n: usize,
+    nb: usize,        // bandwidth
+    tail: usize,      // number of last columns stored in full
+    r: Vec<f64>,      // upper triangular matrix, n rows of nb band and tail values
+    end: Vec<usize>,  // one past the last non-zero column of each row of r
+    nrhs: usize,
+    qtz: Vec<f64>,    // rotated right hand sides, n x nrhs, row major
+    h: Vec<f64>,      // scratch row
+    hz: Vec<f64>,     // scratch right hand sides
+}
+
+impl LeastSquares {
+    /// Least squares problem with `n` unknowns, and observation rows with bandwidth `nb`.
+    pub(crate) fn new(n: usize, nb: usize) -> Self {
+        Self::with_rhs(n, 1, nb, 0)
+    }
+
+    /// Least squares problems with `n` unknowns, and the same observation matrix for `nrhs` right hand sides.
+    ///
+    /// The non-zero coefficients of an observation row should be in `nb` consecutive columns, starting at its first
+    /// non-zero coefficient, or in the last `tail` columns.
+    pub(crate) fn with_rhs(n: usize, nrhs: usize, nb: usize, tail: usize) -> Self {
+        let (nb, tail) = (nb.min(n), tail.min(n));
+        Self {
+            n,
+            nb,
+            tail,
+            r: vec![0.0; n * (nb + tail)],
+            end: (1..=n).collect(),
+            nrhs,
+            qtz: vec![0.0; n * nrhs],
+            h: vec![0.0; n],
+            hz: vec![0.0; nrhs],
+        }
+    }
+
+    // location of element `(i,j)` of `R`, with `j>=i`, in the band, or else in the tail columns
+    fn index(&self, i: usize, j: usize) -> usize {
+        let row = i * (self.nb + self.tail);
+        if j - i < self.nb {
+            row + j - i
+        } else {
+            debug_assert!(j >= self.n - self.tail, "column {j} outside the band of row {i}");
+            row + self.nb + j + self.tail - self.n
+        }
+    }
+
+    // columns right of the diagonal of row `i` of `R`, which can have non-zero values
+    fn columns(&self, i: usize) -> Chain<Range<usize>, Range<usize>> {
+        let end = self.end[i];
+        (i + 1..end.min(i + self.nb)).chain((i + self.nb).max(self.n - self.tail)..end)
+    }
+
+    // diagonal element `i` of `R`
+    fn diagonal(&self, i: usize) -> f64 {
+        self.r[i * (self.nb + self.tail)]
+    }
+
+    /// Adds the observation `sum(a[j]*x[j]) = z`, with weight `w`, and the row coefficients given as pairs of
+    /// column index and value.
+    pub(crate) fn add_row(&mut self, row: impl IntoIterator<Item = (usize, f64)>, z: f64, w: f64) {
+        self.add_row_rhs(row, &[z], w)
+    }
+
+    /// Adds an observation row, as [`add_row`](Self::add_row), with the values `z` for all right hand sides.
+    pub(crate) fn add_row_rhs(&mut self, row: impl IntoIterator<Item = (usize, f64)>, z: &[f64], w: f64) {
+        let mut first = self.n;
+        let mut last = 0;
+        for (j, a) in row {
+            self.h[j] += w * a;
+            first = first.min(j);
+            last = last.max(j + 1);
+        }
+        self.hz.iter_mut().zip(z).for_each(|(hz, z)| *hz = w * z);
+        let mut i = first;
+        while i < last {
+            let piv = self.h[i];
+            self.h[i] = 0.0;
+            if piv != 0.0 {
+                let end = self.end[i].max(last);
+                self.end[i] = end;
+                let ii = self.index(i, i);
+                let d = self.r[ii];
+                let ww = d.hypot(piv);
+                let (cos, sin) = (d / ww, piv / ww);
+                self.r[ii] = ww;
+                for j in self.columns(i) {
+                    let ij = self.index(i, j);
+                    let (rij, hj) = (self.r[ij], self.h[j]);
+                    self.r[ij] = cos * rij + sin * hj;
+                    self.h[j] = cos * hj - sin * rij;
+                }
+                for (qi, hz) in self.qtz[i * self.nrhs..(i + 1) * self.nrhs].iter_mut().zip(self.hz.iter_mut()) {
+                    (*qi, *hz) = (cos * *qi + sin * *hz, cos * *hz - sin * *qi);
+                }
+                last = end;
+            }
+            i += 1;
+        }
+    }
+
+    /// Solves `R x = Q'z` by back substitution, with the solutions for multiple right hand sides arranged as
+    /// `x[i*nrhs+r]`.
+    ///
+    /// Returns `None` if the system is rank deficient, which is the case if a diagonal element of `R` is small
+    /// compared to the largest one.
+    pub(crate) fn solve(&self) -> Option<Vec<f64>> {
+        let (n, nrhs) = (self.n, self.nrhs);
+        if !self.full_rank() {
+            return None;
+        }
+        let mut x = vec![0.0; n * nrhs];
+        for i in (0..n).rev() {
+            let d = self.diagonal(i);
+            for r in 0..nrhs {
+                let s: f64 = self.columns(i).map(|j| self.r[self.index(i, j)] * x[j * nrhs + r]).sum();
+                x[i * nrhs + r] = (self.qtz[i * nrhs + r] - s) / d;
+            }
+        }
+        Some(x)
+    }
+
+    /// Leverage `a'(R'R)^-1 a` of an observation row `a`, given as pairs of column index and value, with the weight
+    /// applied.
+    pub(crate) fn leverage(&self, row: impl IntoIterator<Item = (usize, f64)>) -> f64 {
+        self.forward(row).iter().map(|v| v * v).sum()
+    }
+
+    /// Solution `z` of the normal equations `R'R z = a`, for a row `a` given as pairs of column index and value, by
+    /// forward and back substitution.
+    ///
+    /// Returns `None` if the system is rank deficient, as [`solve`](Self::solve).
+    pub(crate) fn solve_normal(&self, row: impl IntoIterator<Item = (usize, f64)>) -> Option<Vec<f64>> {
+        if !self.full_rank() {
+            return None;
+        }
+        let mut z = self.forward(row);
+        for i in (0..self.n).rev() {
+            let s: f64 = self.columns(i).map(|j| self.r[self.index(i, j)] * z[j]).sum();
+            z[i] = (z[i] - s) / self.diagonal(i);
+        }
+        Some(z)
+    }
+
+    // no diagonal element of `R` small compared to the largest one
+    fn full_rank(&self) -> bool {
+        let dmax = (0..self.n).map(|i| self.diagonal(i).abs()).fold(0.0, f64::max);
+        dmax > 0.0 && (0..self.n).all(|i| self.diagonal(i).abs() > dmax * 1e-10)
+    }
+
+    // solution `v` of `R'v = a`, by forward substitution
+    fn forward(&self, row: impl IntoIterator<Item = (usize, f64)>) -> Vec<f64> {
+        let n = self.n;
+        let mut v = vec![0.0; n];
+        let mut first = n;
+        for (j, a) in row {
+            v[j] += a;
+            first = first.min(j);
+        }
+        for i in first..n {
+            v[i] /= self.diagonal(i);
+            let vi = v[i];
+            if vi != 0.0 {
+                self.columns(i).for_each(|j| v[j] -= self.r[self.index(i, j)] * vi);
+            }
+        }
+        v
+    }
+}
+
+/// Finds the weight `lambda` of the smoothing rows, such that the sum of squared residuals `fp` of the fit equals `s`,
+/// within a relative tolerance of 0.001, using bisection on a logarithmic scale.
+///
+/// The function `fit` returns a fit and its `fp`, which should increase with `lambda`, with `fp < s` for the
+/// least squares fit `lsq` without smoothing, and `fp > s` for large values of `lambda`.
+/// Returns the fit found, or the last fit with `fp < s` if the iteration limit is reached.
+pub(crate) fn smoothing_weight<T, E>(lsq: (T, f64), s: f64, fit: impl Fn(f64) -> Result<(T, f64), E>) -> Result<(T, f64), E> {
+    let (mut lo, mut hi) = (0.0, f64::INFINITY);
+    let mut lambda = 1.0;
+    let mut best = lsq;
+    for _ in 0..MAXIT {
+        let (sp, fp) = fit(lambda)?;
+        if (fp - s).abs() <= TOL * s {
+            return Ok((sp, fp));
+        }
+        if fp < s {
+            lo = lambda;
+            best = (sp, fp);
+        } else {
+            hi = lambda;
+        }
+        lambda = if hi.is_infinite() {
+            lambda * 10.0
+        } else if lo == 0.0 {
+            lambda / 10.0
+        } else {
+            (lo * hi).sqrt()
+        };
+    }
+    Ok(best)
+}
@@ -0,0 +1,120 @@
+//! Monotone Spline Fits
+//!
+//! A spline with B-spline coefficients in ascending order is non-decreasing over its full domain, as its derivative
+//! is a spline with coefficients `K*(c[j+1]-c[j])/(t[j+K+1]-t[j+1])`. The monotone fits of `SplineCurveFit` use this
+//! sufficient condition: for given knots, the coefficients are fitted by weighted least squares with the ordering
+//! constraints `c[j] <= c[j+1]` (or `c[j] >= c[j+1]`), using the active set method of Lawson and Hanson.
+//!
+//! Active constraints merge neighbouring coefficients into a single unknown, which keeps the observation matrix of
+//! the remaining unknowns banded, and the least squares problems are solved with the same Givens rotations as the
+//! other fits implemented in Rust.
+
+use crate::bspline::{basis, interval, MAX_ORDER};
+use crate::lsq::LeastSquares;
+use crate::{FitError, FitResult};
+
+// relative tolerance of the optimality test
+const TOL: f64 = 1E-10;
+
+/// Direction of a monotone fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monotonicity {
+    /// Non-decreasing spline.
+    Increasing,
+    /// Non-increasing spline.
+    Decreasing,
+}
+
+impl Monotonicity {
+    fn sign(self) -> f64 {
+        match self {
+            Self::Increasing => 1.0,
+            Self::Decreasing => -1.0,
+        }
+    }
+}
+
+/// Weighted least squares coefficients of a spline with knots `t` and degree `k`, for data `x`, `y` and weights `w`,
+/// constrained to be monotone.
+pub(crate) fn monotone_coefficients(
+    routine: &'static str,
+    t: &[f64],
+    k: usize,
+    (x, y, w): (&[f64], &[f64], &[f64]),
+    monotonicity: Monotonicity,
+) -> FitResult<Vec<f64>> {
+    let nc = t.len() - k - 1;
+    let sign = monotonicity.sign();
+    let rows: Vec<(usize, [f64; MAX_ORDER])> = x
+        .iter()
+        .map(|&x| {
+            let l = interval(t, k, x);
+            (l - k, basis(t, k, x, l))
+        })
+        .collect();
+
+    // least squares fit, with coefficients j and j+1 merged for all constraints j which are not split
+    let solve = |split: &[bool]| -> FitResult<Vec<f64>> {
+        let group: Vec<usize> = std::iter::once(0)
+            .chain(split.iter().scan(0, |g, &s| {
+                *g += s as usize;
+                Some(*g)
+            }))
+            .collect();
+        let mut lsq = LeastSquares::new(group[nc - 1] + 1, k + 1);
+        for ((j, b), (&y, &w)) in rows.iter().zip(y.iter().zip(w)) {
+            lsq.add_row((0..=k).map(|a| (group[j + a], b[a])), y, w);
+        }
+        let cg = lsq.solve().ok_or(FitError::RankDeficient { routine })?;
+        Ok(group.iter().map(|&g| cg[g]).collect())
+    };
+
+    let scale: f64 = y.iter().zip(w).map(|(y, w)| w * w * y.abs()).sum();
+    let mut split = vec![false; nc - 1];
+    let mut c = solve(&split)?;
+    for _ in 0..3 * nc {
+        // gradient of half the sum of squared residuals, with respect to the increments of the coefficients
+        let mut g = vec![0.0; nc];
+        for ((j, b), (&y, &w)) in rows.iter().zip(y.iter().zip(w)) {
+            let r = y - (0..=k).map(|a| b[a] * c[j + a]).sum::<f64>();
+            (0..=k).for_each(|a| g[j + a] += w * w * r * b[a]);
+        }
+        let mut tail = 0.0;
+        let mut best: Option<(usize, f64)> = None;
+        for i in (0..nc - 1).rev() {
+            tail += g[i + 1];
+            let d = sign * tail;
+            if !split[i] && d > TOL * scale && best.is_none_or(|(_, b)| d > b) {
+                best = Some((i, d));
+            }
+        }
+        let Some((i, _)) = best else {
+            return Ok(c);
+        };
+        split[i] = true;
+        loop {
+            let z = solve(&split)?;
+            // step towards z, up to the first constraint which becomes active
+            let step = (0..nc - 1)
+                .filter(|&i| split[i])
+                .filter_map(|i| {
+                    let (dc, dz) = (sign * (c[i + 1] - c[i]), sign * (z[i + 1] - z[i]));
+                    // skip increments which stay feasible, or do not move, as these would give a NaN step
+                    (dz <= 0.0 && dc - dz > 0.0).then(|| (i, (dc / (dc - dz)).clamp(0.0, 1.0)))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match step {
+                None => {
+                    c = z;
+                    break;
+                }
+                Some((i, alpha)) => {
+                    c.iter_mut().zip(&z).for_each(|(c, z)| *c += alpha * (z - *c));
+                    split[i] = false;
+                    (0..nc - 1).for_each(|i| split[i] &= sign * (c[i + 1] - c[i]) > 0.0);
+                }
+            }
+        }
+    }
+    Err(FitError::NotConverged { routine, iterations: 3 * nc })
+}
@@ -0,0 +1,82 @@
+//! Multiple Series Fits, with Shared Knots
+//!
+//! Fits `M` data series, with a common set of x values, to splines with a single knot vector, for example a set of
+//! spectra measured on the same wavelength grid. As all splines have the same knots, their coefficients can be
+//! compared directly, and stored as a matrix.
+//!
+//! The series are stacked into a curve in `M` dimensional space, with the x values as curve parameter, and fitted
+//! with Dierckx' `concur`, using `ParameterSplineCurveFit<K,M>`; its knots are placed where any of the series needs
+//! them. As `concur`, this supports degrees 1, 3 and 5, and up to 10 series.
+
+use crate::{validate, FitError, FitReport, FitResult, FittedCurve, ParameterSplineCurveFit};
+use spliny::SplineCurve;
+
+/// Fits `M` data series, with common x values, to splines of degree `K` with shared knots.
+#[derive(Clone)]
+pub struct MultiSeriesFit<const K: usize, const M: usize> {
+    fit: ParameterSplineCurveFit<K, M>,
+    w: Vec<f64>, // weights, for the fit reports of the series
+}
+
+impl<const K: usize, const M: usize> MultiSeriesFit<K, M> {
+    /**
+     Constructor, with the common x values, in strictly ascending order, and `M` series of y values, all with the
+     same length as x.
+     */
+    pub fn new(x: Vec<f64>, y: Vec<Vec<f64>>) -> FitResult<Self> {
+        if y.len() != M {
+            return Err(FitError::SizeMismatch { routine: "concur", what: "series", expected: M, found: y.len() });
+        }
+        for y in &y {
+            validate::size("concur", "y", y, x.len())?;
+        }
+        let xn = (0..x.len()).flat_map(|i| y.iter().map(move |y| y[i])).collect();
+        let w = vec![1.0; x.len()];
+        Ok(Self { fit: ParameterSplineCurveFit::new(x, xn)?, w })
+    }
+
+    /// Sets the weights of the x values, which apply to all series, and should be finite and larger than zero.
+    pub fn set_weights(self, weights: Vec<f64>) -> FitResult<Self> {
+        Ok(Self { fit: self.fit.weights(weights.clone())?, w: weights })
+    }
+
+    /**
+     * Cardinal Splines: Weighted least squares splines with equidistant knots
+     *
+     * All splines have the same knots, dt (input parameter) apart, and aligned to integer multiples of it.
+     */
+    pub fn cardinal_spline(self, dt: f64) -> FitResult<Vec<FittedCurve<K, 1>>> {
+        Ok(split(self.fit.cardinal_spline(dt)?, &self.w))
+    }
+
+    /**
+     * Smoothing Splines, with shared knots
+     *
+     * The number of knots is the minimal number for which the root mean square error of all values of all series is
+     * less than the specified rms value; the errors of the individual series are reported in their fit reports.
+     *
+     * Arguments:
+     * - rms: root mean square error
+     */
+    pub fn smoothing_spline(self, rms: f64) -> FitResult<Vec<FittedCurve<K, 1>>> {
+        // concur uses the lengths of the residual vectors of the M dimensional curve
+        Ok(split(self.fit.smoothing_spline(rms * (M as f64).sqrt())?, &self.w))
+    }
+}
+
+// splits a fit of M stacked series into fits of the individual series, with the same knots
+fn split<const K: usize, const M: usize>(fit: FittedCurve<K, M>, w: &[f64]) -> Vec<FittedCurve<K, 1>> {
+    let FittedCurve { spline, report } = fit;
+    let nb = spline.t.len() - K - 1;
+    let m = w.len();
+    (0..M)
+        .map(|d| {
+            let residuals: Vec<f64> = report.residuals.iter().skip(d).step_by(M).copied().collect();
+            let fp = residuals.iter().zip(w).map(|(r, w)| (w * r).powi(2)).sum::<f64>();
+            FittedCurve {
+                spline: SplineCurve::new(spline.t.clone(), spline.c[d * nb..(d + 1) * nb].to_vec()),
+                report: FitReport { fp, e_rms: (fp / m as f64).sqrt(), residuals, ..report.clone() },
+            }
+        })
+        .collect()
+}
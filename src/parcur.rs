@@ -0,0 +1,329 @@
+//! Unconstrained Curve (K-Degree) Spline-Fit for Multi-Dimensional (N) Data
+//!
+//! Rust wrapper of Dierckx' `parcur` Fortran subroutine:
+//!
+//! ```fortran
+//!  subroutine parcur(iopt,ipar,idim,m,u,mx,x,w,ub,ue,k,s,nest,n,t,nc,c,fp,wrk,lwrk,iwrk,ier)
+//! ```
+//! Different from `concur`, as wrapped by `ParameterSplineCurveFit<K,N>`, `parcur` does not support end point
+//! constraints. `AutoParameterSplineCurveFit<K,N>` fits curves without given parameter values, using the cumulative
+//! distance between the data points, normalized to a range from 0.0 to 1.0, as calculated by `parcur` for `ipar=0`.
+//! The fit methods of `AutoParameterSplineCurveFit<K,N>` return the spline together with the parameter values
+//! used, to map the data points to the curve.
+
+use std::iter::repeat_n;
+use crate::dierckx::parcur_;
+use super::{validate, FitError, FitResult, FittedCurve};
+use crate::clocur::chord_lengths;
+use crate::curfit::cardinal_knots;
+use crate::select::{Edf, Smoother};
+use std::mem::take;
+use spliny::SplineCurve;
+
+#[derive(Clone)]
+pub struct AutoParameterSplineCurveFit<const K: usize, const N: usize> {
+    // input values
+    xn: Vec<f64>, // data (x,y,..) coordinates
+    u: Vec<f64>,
+    w: Vec<f64>, // weight factors
+    ub: f64,
+    ue: f64,
+    ipar: i32, // 0 for parameter values calculated by parcur, 1 if given
+
+    t: Vec<f64>,
+    c: Vec<f64>,
+    e_rms: Option<f64>,
+    ier: i32,
+    edf: bool, // calculate the effective degrees of freedom of smoothing fits
+    n: i32,
+
+    // work space values
+    wrk: Vec<f64>,  // used for successive tries
+    iwrk: Vec<i32>, // used for successive tries
+    m: i32,
+    mx: i32,
+    nest: i32,
+    k: i32,
+    idim: i32,
+}
+
+/**
+
+Fit parametric B-Spline curve to a set of coordinates, with optional automatic parameterization
+
+Wrapper for Dierckx' `parcur` subroutine.
+
+*/
+impl<const K: usize, const N: usize> AutoParameterSplineCurveFit<K, N> {
+    /// Constructor, taking the curve coordinates as input, with the curve parameter values calculated by `parcur`
+    /// (`ipar=0`), as the cumulative distance between the data points, normalized to a range from 0.0 to 1.0.
+    ///
+    /// Coordinates are represented by the vector `xn`, starting with the coordinates of the first point;
+    /// for example, if N=3, a three dimensional space, with coordinates given as (x,y,z), the array can be
+    /// constructed as [x0, y0, z0, x1, y1, z1, x2 ...]. Consecutive points should not coincide, as their
+    /// parameter values would not be strictly ascending.
+    pub fn new(xn: Vec<f64>) -> FitResult<Self> {
+        Self::build(None, xn)
+    }
+
+    /// Constructor, taking as input curve parameter values `u`, in strictly ascending order, and curve coordinates
+    /// `xn`, as for [`ParameterSplineCurveFit::new`](crate::ParameterSplineCurveFit::new).
+    pub fn with_parameters(u: Vec<f64>, xn: Vec<f64>) -> FitResult<Self> {
+        Self::build(Some(u), xn)
+    }
+
+    fn build(u: Option<Vec<f64>>, xn: Vec<f64>) -> FitResult<Self> {
+        let k = K as i32;
+        if !(1..=5).contains(&K) {
+            return Err(FitError::InvalidDegree { routine: "parcur", k: K, allowed: "1 to 5" });
+        };
+        let idim = if (1..=10).contains(&N) {
+            N as i32
+        } else {
+            return Err(FitError::InvalidDimension { routine: "parcur", n: N });
+        };
+        let m = u.as_ref().map_or(xn.len() / N, Vec::len) as i32;
+        if m <= k {
+            return Err(FitError::TooFewDataPoints { routine: "parcur", found: m as usize, min: K + 1 });
+        };
+        let mx = m * idim;
+        if xn.len() as i32 != mx {
+            return Err(FitError::SizeMismatch { routine: "parcur", what: "xn", expected: mx as usize, found: xn.len() });
+        }
+        validate::finite("parcur", "xn", &xn)?;
+        let (u, ipar) = match u {
+            Some(u) => {
+                validate::strictly_ascending("parcur", "u", &u)?;
+                (u, 1)
+            }
+            None => {
+                validate::strictly_ascending("parcur", "u", &chord_lengths::<N>(&xn))?;
+                (vec![0.0; m as usize], 0) // calculated by parcur, with range 0.0 to 1.0
+            }
+        };
+        let (ub, ue) = if ipar == 1 { (u[0], u[m as usize - 1]) } else { (0.0, 1.0) };
+        let w_vec = vec![1.0; m as usize];
+
+        let nest = m + k + 1;
+        let n = nest;
+        let t_vec = vec![0.0; nest as usize];
+        let c_vec = vec![0.0; (nest * idim) as usize];
+
+        let iwrk_vec = vec![0i32; nest as usize];
+
+        let lwrk = m * (k + 1) + nest * (6 + idim + 3 * k);
+        let wrk_vec = vec![0f64; lwrk as usize];
+
+        Ok(Self {
+            u,
+            xn,
+            w: w_vec,
+            ub,
+            ue,
+            ipar,
+            t: t_vec,
+            c: c_vec,
+            wrk: wrk_vec,
+            iwrk: iwrk_vec,
+            m,
+            mx,
+            nest,
+            k,
+            idim,
+            n,
+            e_rms: None,
+            ier: 0,
+            edf: false,
+        })
+    }
+
+    /// Curve parameter values: in case of automatic parameterization these are calculated by `parcur` in a fit, and
+    /// returned by the fit methods.
+    pub fn u(&self) -> &[f64] {
+        &self.u
+    }
+
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn weights(mut self, weights: Vec<f64>) -> FitResult<Self> {
+        validate::weights("parcur", &weights, self.u.len())?;
+        self.w = weights;
+        Ok(self)
+    }
+
+    /// Calculates the `edf` of smoothing fits, see [`select`](crate::select#effective-degrees-of-freedom).
+    pub fn with_edf(mut self) -> Self {
+        self.edf = true;
+        self
+    }
+
+    fn parcur(&mut self, iopt: i32, e_rms: Option<f64>, knots: Option<Vec<f64>>) -> FitResult<()> {
+        let mut fp = 0.0;
+        let s = if let Some(e) = e_rms {
+            self.m as f64 * e.powi(2)
+        } else {
+            0.0
+        };
+
+        let nc = self.c.len() as i32;
+        let lwrk = self.wrk.len() as i32;
+
+        if let Some(knots) = knots {
+            self.n = knots.len() as i32;
+            self.t = knots;
+            self.t.resize(self.nest as usize, 0.0);
+        }
+        let mut ierr = 0;
+        unsafe {
+            parcur_(
+                &iopt,
+                &self.ipar,
+                &self.idim,
+                &self.m,
+                self.u.as_mut_ptr(),
+                &self.mx,
+                self.xn.as_ptr(),
+                self.w.as_ptr(),
+                &mut self.ub,
+                &mut self.ue,
+                &self.k,
+                &s,
+                &self.nest,
+                &mut self.n,
+                self.t.as_mut_ptr(),
+                &nc,
+                self.c.as_mut_ptr(),
+                &mut fp,
+                self.wrk.as_mut_ptr(),
+                &lwrk,
+                self.iwrk.as_mut_ptr(),
+                &mut ierr,
+            );
+        }
+        self.e_rms = Some((fp / self.m as f64).sqrt());
+        self.ier = ierr;
+        if ierr <= 0 {
+            Ok(())
+        } else {
+            Err(FitError::from_ier("parcur", ierr, self.nest, s))
+        }
+    }
+
+    // spline and fit report of the last fit, with `iterations` the number of fits calculated, and the curve
+    // parameter values
+    fn fitted(mut self, iterations: usize, edf: Edf) -> (FittedCurve<K, N>, Vec<f64>) {
+        let (u, xn, w) = (take(&mut self.u), take(&mut self.xn), take(&mut self.w));
+        let ier = self.ier;
+        let spline: SplineCurve<K, N> = self.into();
+        let smoother = Smoother {
+            routine: "parcur",
+            u: &u,
+            xn: &xn,
+            w: &w,
+            fixed: (0, 0),
+            periodic: false,
+            constraints: 0,
+        };
+        let report = smoother.report(&spline, ier, iterations, edf);
+        (FittedCurve { spline, report }, u)
+    }
+
+    /**
+     * Cardinal Spline: Weighted least squares spline with equidistant knots
+     *
+     * Returns Spline, and the curve parameter values, with knots dt (input parameter) apart,
+     * and aligned to integer multiples of it. For automatic parameterization the parameter values
+     * range from 0.0 to 1.0.
+     */
+    pub fn cardinal_spline(mut self, dt: f64) -> FitResult<(FittedCurve<K, N>, Vec<f64>)> {
+        let knots = cardinal_knots("parcur", self.ub, self.ue, dt)?;
+        let t: Vec<f64> = repeat_n(self.ub, K + 1) // begin padding, needed for spline evaluation
+            .chain(knots)
+            .chain(repeat_n(self.ue, K + 1)) // end padding
+            .collect();
+        if t.len() > self.nest as usize {
+            return Err(FitError::KnotSpacing { routine: "parcur", dt });
+        }
+
+        self.parcur(-1, Some(0.0), Some(t))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
+    }
+
+    /**
+    Interpolating Spline, returned together with the curve parameter values.
+    */
+    pub fn interpolating_spline(mut self) -> FitResult<(FittedCurve<K, N>, Vec<f64>)> {
+        self.parcur(0, Some(0.0), None)?;
+        Ok(self.fitted(1, Edf::LeastSquares))
+    }
+
+    /**
+     * Fit a smoothing spline, returned together with the curve parameter values.
+     *
+     * Arguments:
+     * - rms: root mean square error
+     *
+     */
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<(FittedCurve<K, N>, Vec<f64>)> {
+        self.parcur(0, Some(rms), None)?;
+        let edf = Edf::Smoothing(self.edf);
+        Ok(self.fitted(1, edf))
+    }
+
+    /**
+     * Fit a best-fit smoothing spline by decreasing rms target, returned together with the curve parameter values.
+     *
+     * Arguments:
+     * - rms_start: root mean square error start value
+     * - rms_scale_ratio: `rms *= rms_scale_ratio` for each step
+     * - converged: boolean convergence function, with arguments
+     *   - number of knots (usize),
+     *   - number of added knots in the last iteration (usize)
+     *   - root mean square error of the fit (f64),
+     *   - root mean square error improvement in the last iteration
+     * - n_iter: number of iterations
+     *
+     */
+    pub fn smoothing_spline_optimize(
+        mut self,
+        rms_start: f64,
+        rms_scale_ratio: f64,
+        converged: impl Fn(i32, i32, f64, f64) -> bool,
+        n_iter: Option<usize>,
+    ) -> FitResult<(FittedCurve<K, N>, Vec<f64>)> {
+        let n_iter = n_iter.unwrap_or(40);
+        self.parcur(0, Some(rms_start), None)?;
+        let mut rms = self.e_rms.unwrap();
+        let mut n_prev;
+        let mut rms_prev;
+        for i in 0..n_iter {
+            n_prev = self.n;
+            rms_prev = rms;
+            self.parcur(1, Some(rms * rms_scale_ratio), None)?;
+            rms = self.e_rms.unwrap();
+            if converged(self.n, self.n - n_prev, rms, rms_prev - rms) {
+                self.parcur(0, Some(rms_prev), None)?;
+                let edf = Edf::Smoothing(self.edf);
+                return Ok(self.fitted(i + 3, edf));
+            };
+        }
+        Err(FitError::NotConverged { routine: "parcur", iterations: n_iter })
+    }
+} // impl AutoParameterSplineCurveFit
+
+impl<const K: usize, const N: usize> From<AutoParameterSplineCurveFit<K, N>> for SplineCurve<K, N> {
+    fn from(mut sp: AutoParameterSplineCurveFit<K, N>) -> Self {
+        sp.t.truncate(sp.n as usize);
+        sp.t.shrink_to_fit();
+
+        sp.c.truncate((sp.n * sp.idim) as usize); // this is the size as returned, but this conains K+1 unused values at the end
+
+        for dim in 0..sp.idim as usize {
+            let ib = (dim + 1) * (sp.n - sp.k - 1) as usize;
+            let ie = ib + sp.k as usize + 1;
+            sp.c.drain(ib..ie);
+        }
+        sp.c.shrink_to_fit();
+
+        Self::new(sp.t, sp.c)
+    }
+}
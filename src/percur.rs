@@ -0,0 +1,246 @@
+//! Periodic (K-Degree) Spline-Fit for Single Output Data
+//!
+//! Fits periodic splines to a set of data points (x<sub>i</sub>,y<sub>i</sub>), with period x<sub>m</sub>-x<sub>1</sub>,
+//! as done by Dierckx' `percur` Fortran subroutine:
+//!
+//! ```fortran
+//!  subroutine percur(iopt,m,x,y,w,k,s,nest,n,t,c,fp,wrk,lwrk,iwrk,ier)
+//! ```
+//! As `percur` is not part of the `dierckx-sys` library, the fits are calculated with `clocur`, using a one dimensional
+//! curve, and the values x<sub>i</sub> as curve parameter. This fits a periodic spline y(x) to the same data, but its
+//! knots and coefficients have not been compared with those calculated by `percur`.
+//! As in `percur`, the last data point marks the end of the period, and its weight is not used; its `y` value should
+//! be equal to the first. Errors are reported for routine `"clocur"`, which calculates the fits.
+
+use std::iter::repeat_n;
+use super::{validate, FitError, FitResult, FittedCurve};
+use crate::curfit::cardinal_knots;
+use crate::select::{Edf, Smoother};
+use std::mem::take;
+use dierckx_sys::clocur_;
+use spliny::SplineCurve;
+
+#[derive(Clone)]
+pub struct PeriodicSplineCurveFit<const K: usize> {
+    // input values
+    x: Vec<f64>, // data x coordinates
+    y: Vec<f64>, // data y coordinates
+    w: Vec<f64>, // weight factors
+
+    t: Vec<f64>,
+    c: Vec<f64>,
+    e_rms: Option<f64>,
+    ier: i32,
+    edf: bool, // calculate the effective degrees of freedom of smoothing fits
+    n: i32,
+
+    // work space values
+    wrk: Vec<f64>,  // used for successive tries
+    iwrk: Vec<i32>, // used for successive tries
+    m: i32,
+    nest: i32,
+    k: i32,
+}
+
+/**
+
+Fit periodic B-Spline function to a set of data points
+
+As Dierckx' `percur` subroutine, calculated by `clocur`.
+
+*/
+impl<const K: usize> PeriodicSplineCurveFit<K> {
+    /// Constructor, with inputs x and y vectors, which should have equal length.
+    ///
+    /// The values x should be in strictly ascending order, with the period of the spline given by the range of x:
+    /// the last value of x marks the end of the period, and the last value of y should be equal to the first value,
+    /// apart from rounding errors.
+    pub fn new(x: Vec<f64>, mut y: Vec<f64>) -> FitResult<Self> {
+        let k = K as i32;
+        if !(1..=5).contains(&K) {
+            return Err(FitError::InvalidDegree { routine: "clocur", k: K, allowed: "1 to 5" });
+        };
+        let m = x.len() as i32;
+        if m < 2 {
+            return Err(FitError::TooFewDataPoints { routine: "clocur", found: m as usize, min: 2 });
+        };
+        if y.len() != x.len() {
+            return Err(FitError::SizeMismatch { routine: "clocur", what: "y", expected: x.len(), found: y.len() });
+        }
+        validate::strictly_ascending("clocur", "x", &x)?;
+        validate::finite("clocur", "y", &y)?;
+        let scale = y.iter().fold(1.0f64, |s, y| s.max(y.abs()));
+        if (y[m as usize - 1] - y[0]).abs() > 1E-9 * scale {
+            return Err(FitError::InvalidInput {
+                routine: "clocur",
+                reason: "the last y value should be equal to the first, as it marks the end of the period".to_string(),
+            });
+        }
+        y[m as usize - 1] = y[0]; // clocur requires the first and last data points to be exactly equal
+
+        let w_vec = vec![1.0; m as usize];
+
+        let nest = m + 2 * k;
+        let n = 0;
+        let t_vec = vec![0.0; nest as usize];
+        let c_vec = vec![0.0; nest as usize];
+
+        let iwrk_vec = vec![0i32; nest as usize];
+
+        let lwrk = m * (k + 1) + nest * (8 + 5 * k);
+        let wrk_vec = vec![0f64; lwrk as usize];
+
+        Ok(Self {
+            x,
+            y,
+            w: w_vec,
+            t: t_vec,
+            c: c_vec,
+            wrk: wrk_vec,
+            iwrk: iwrk_vec,
+            m,
+            nest,
+            k,
+            n,
+            e_rms: None,
+            ier: 0,
+            edf: false,
+        })
+    }
+
+    /// Sets the weights of the data points, which should be finite and larger than zero.
+    pub fn set_weights(mut self, weights: Vec<f64>) -> FitResult<Self> {
+        validate::weights("clocur", &weights, self.x.len())?;
+        self.w = weights;
+        Ok(self)
+    }
+
+    /// Calculates the `edf` of smoothing fits, see [`select`](crate::select#effective-degrees-of-freedom).
+    pub fn with_edf(mut self) -> Self {
+        self.edf = true;
+        self
+    }
+
+    fn percur(&mut self, iopt: i32, e_rms: Option<f64>) -> FitResult<()> {
+        let mut fp = 0.0;
+        let s = if let Some(e) = e_rms {
+            self.m as f64 * e.powi(2)
+        } else {
+            0.0
+        };
+
+        let ipar = 1;
+        let idim = 1;
+        let nc = self.c.len() as i32;
+        let lwrk = self.wrk.len() as i32;
+
+        let mut ierr = 0;
+        unsafe {
+            clocur_(
+                &iopt,
+                &ipar,
+                &idim,
+                &self.m,
+                self.x.as_ptr(),
+                &self.m,
+                self.y.as_ptr(),
+                self.w.as_ptr(),
+                &self.k,
+                &s,
+                &self.nest,
+                &mut self.n,
+                self.t.as_mut_ptr(),
+                &nc,
+                self.c.as_mut_ptr(),
+                &mut fp,
+                self.wrk.as_mut_ptr(),
+                &lwrk,
+                self.iwrk.as_mut_ptr(),
+                &mut ierr,
+            );
+        }
+        self.e_rms = Some((fp / self.m as f64).sqrt());
+        self.ier = ierr;
+        if ierr <= 0 {
+            Ok(())
+        } else {
+            Err(FitError::from_ier("clocur", ierr, self.nest, s))
+        }
+    }
+
+    // periodic spline y(x) and fit report of the last fit, with `iterations` the number of fits calculated
+    fn fitted(mut self, iterations: usize, edf: Edf) -> FittedCurve<K, 1> {
+        let (x, y, w) = (take(&mut self.x), take(&mut self.y), take(&mut self.w));
+        let ier = self.ier;
+        let spline: SplineCurve<K, 1> = self.into();
+        let smoother = Smoother {
+            routine: "clocur",
+            u: &x,
+            xn: &y,
+            w: &w,
+            fixed: (0, 0),
+            periodic: true,
+            constraints: 0,
+        };
+        let report = smoother.report(&spline, ier, iterations, edf);
+        FittedCurve { spline, report }
+    }
+
+    /**
+     * Cardinal Spline: Weighted least squares periodic spline with equidistant knots
+     *
+     * Returns Spline, with interior knots dt (input parameter) apart,
+     * and aligned to integer multiples of it. The boundary knots are
+     * placed by `clocur`, to obtain a spline with the period of the data.
+     */
+    pub fn cardinal_spline(mut self, dt: f64) -> FitResult<FittedCurve<K, 1>> {
+        let m = self.x.len();
+        let knots = cardinal_knots("clocur", self.x[0], self.x[m - 1], dt)?;
+        let n = knots.len();
+        if n + 2 * (K + 1) > self.nest as usize {
+            return Err(FitError::KnotSpacing { routine: "clocur", dt });
+        };
+
+        self.t.clear();
+        self.t.extend(
+            repeat_n(0.0, K + 1) // begin padding, set by clocur
+                .chain(knots)
+        );
+        self.t.resize(self.nest as usize, 0.0);
+        self.n = (n + 2 * (K + 1)) as i32;
+
+        self.percur(-1, Some(0.0))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
+    }
+
+    /**
+    Interpolating Spline
+    */
+    pub fn interpolating_spline(mut self) -> FitResult<FittedCurve<K, 1>> {
+        self.percur(0, Some(0.0))?;
+        Ok(self.fitted(1, Edf::LeastSquares))
+    }
+
+    /**
+     * Smoothing Spline
+     *
+     * A periodic spline with a minimal number of knots, with error less than the specifed rms value.
+     */
+    pub fn smoothing_spline(mut self, rms: f64) -> FitResult<FittedCurve<K, 1>> {
+        self.percur(0, Some(rms))?;
+        let edf = Edf::Smoothing(self.edf);
+        Ok(self.fitted(1, edf))
+    }
+} // impl PeriodicSplineCurveFit
+
+impl<const K: usize> From<PeriodicSplineCurveFit<K>> for SplineCurve<K, 1> {
+    fn from(mut sp: PeriodicSplineCurveFit<K>) -> Self {
+        sp.t.truncate(sp.n as usize); // knots, including the periodic extension beyond the data range
+        sp.t.shrink_to_fit();
+
+        sp.c.truncate(sp.n as usize - (K + 1));
+        sp.c.shrink_to_fit();
+
+        Self::new(sp.t, sp.c)
+    }
+}
@@ -0,0 +1,76 @@
+//! Fit diagnostics, returned together with the fitted spline curves.
+
+use spliny::SplineCurve;
+use std::ops::Deref;
+
+/// Diagnostics of a spline curve fit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitReport {
+    /// Name of the Dierckx routine which calculated the fit, such as `"curfit"` or `"concon"`, which determines the
+    /// meaning of `ier`.
+    pub routine: &'static str,
+    /// Weighted sum of squared residuals, `fp` in Dierckx' routines.
+    pub fp: f64,
+    /// Root mean square error, `sqrt(fp/m)`, with `m` the number of data points used.
+    pub e_rms: f64,
+    /// Residuals, data values minus spline values, for all data points, arranged as the data, with `N` values per
+    /// point.
+    pub residuals: Vec<f64>,
+    /// Number of knots of the spline.
+    pub knots: usize,
+    /// Dierckx error flag of the last fit, 0 for a normal return; negative values are warnings, with a meaning which
+    /// depends on the routine, as described by [`warning`](Self::warning). Fits of which the coefficients are changed
+    /// by this library after the Dierckx fit, such as monotone fits, report 0.
+    pub ier: i32,
+    /// Effective degrees of freedom: the number of free coefficients for least squares fits, including smoothing
+    /// fits returned as least squares splines (`ier` -1 or -2), or the trace of the smoother matrix for smoothing
+    /// fits. The latter requires a refit of the data in Rust, and is only calculated if requested by the `with_edf`
+    /// method of the fit. Not available for constrained fits, such as convexity constrained and monotone fits.
+    pub edf: Option<f64>,
+    /// Number of fits calculated by the Dierckx routine, which is larger than one for the optimize methods.
+    pub iterations: usize,
+}
+
+impl FitReport {
+    /// Description of the warning code `ier` of the Dierckx routine, if not zero.
+    pub fn warning(&self) -> Option<&'static str> {
+        match (self.routine, self.ier) {
+            ("concon", -1) => Some("adding knots does not reduce the error any further: the error is larger than the \
+                target rms value, which may be too small"),
+            ("concon", -2) => Some("the maximal number of knots m+4 has been reached: the error is larger than the \
+                target rms value, which may be too small"),
+            ("concon", -3) => Some("out of storage space for knots (nest): the error of the least squares spline with \
+                nest knots is larger than the target rms value"),
+            ("concon", _) => None,
+            (_, -1) => Some("interpolating spline (fp=0)"),
+            ("clocur", -2) => Some("weighted least squares constant, returned as its error is less than the \
+                target rms value"),
+            ("curfit" | "concur" | "parcur", -2) => Some("weighted least squares polynomial of degree k, returned as \
+                its error is less than the target rms value"),
+            _ => None,
+        }
+    }
+}
+
+/// A fitted spline curve, together with its fit diagnostics.
+///
+/// Dereferences to the spline, and converts into it with `into`, or by taking its `spline` field.
+#[derive(Debug, Clone)]
+pub struct FittedCurve<const K: usize, const N: usize> {
+    pub spline: SplineCurve<K, N>,
+    pub report: FitReport,
+}
+
+impl<const K: usize, const N: usize> Deref for FittedCurve<K, N> {
+    type Target = SplineCurve<K, N>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.spline
+    }
+}
+
+impl<const K: usize, const N: usize> From<FittedCurve<K, N>> for SplineCurve<K, N> {
+    fn from(fit: FittedCurve<K, N>) -> Self {
+        fit.spline
+    }
+}
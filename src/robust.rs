@@ -0,0 +1,111 @@
+//! Robust Smoothing Spline Fits
+//!
+//! Smoothing splines are least squares fits, and a single spike in the data can pull the spline far away from the
+//! other data points. The `robust_smoothing_spline` methods of `SplineCurveFit` and `ParameterSplineCurveFit` use
+//! iteratively reweighted least squares: after each smoothing fit the data points are down-weighted according to
+//! the size of their residuals, and the fit is repeated with the new weights, until the weights do not change anymore.
+//!
+//! The residuals are scaled by a robust estimate of their standard deviation, the median absolute residual divided
+//! by 0.6745, and the robustness weights are calculated from the scaled residuals `u` by a [`RobustLoss`] function.
+//! For `N>1` the residual of a point is the length of its residual vector. The weights supplied by the fitter's
+//! weight method are multiplied by the square root of the robustness weights, as Dierckx' weights multiply the
+//! residuals, not their squares.
+
+use crate::{FitError, FitResult, FittedCurve};
+
+// relative change of the robustness weights at convergence
+const TOL: f64 = 1E-3;
+// lower bound of the robustness weights, as Dierckx' routines require positive weights
+const MIN_WEIGHT: f64 = 1E-8;
+// points with a robustness weight below this value are reported as outliers
+const OUTLIER_WEIGHT: f64 = 0.5;
+
+/// Loss function of a robust fit, with its tuning constant, in units of the robust residual scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RobustLoss {
+    /// Huber loss, with weight 1 for `|u|<=c`, and `c/|u|` otherwise; a common value of `c` is 1.345.
+    Huber(f64),
+    /// Tukey's bisquare loss, with weight `(1-(u/c)^2)^2` for `|u|<c`, and 0 otherwise; a common value of `c` is 4.685.
+    Bisquare(f64),
+}
+
+impl RobustLoss {
+    /// Huber loss with tuning constant 1.345, which has an efficiency of 95% for normally distributed errors.
+    pub fn huber() -> Self {
+        Self::Huber(1.345)
+    }
+
+    /// Bisquare loss with tuning constant 4.685, which has an efficiency of 95% for normally distributed errors.
+    pub fn bisquare() -> Self {
+        Self::Bisquare(4.685)
+    }
+
+    /// Robustness weight for a scaled residual `u`.
+    pub fn weight(&self, u: f64) -> f64 {
+        let u = u.abs();
+        match *self {
+            Self::Huber(c) => if u <= c { 1.0 } else { c / u },
+            Self::Bisquare(c) => if u < c { (1.0 - (u / c).powi(2)).powi(2) } else { 0.0 },
+        }
+    }
+}
+
+/// Result of a robust fit.
+#[derive(Debug, Clone)]
+pub struct RobustFit<const K: usize, const N: usize> {
+    /// The spline and its fit report, for the last fit, with the final weights.
+    pub fit: FittedCurve<K, N>,
+    /// Robustness weights of the data points, between 0 and 1, which multiply the squared residuals.
+    pub weights: Vec<f64>,
+    /// Indices of the data points with a robustness weight below 0.5.
+    pub outliers: Vec<usize>,
+    /// Robust estimate of the standard deviation of the residuals of the last fit.
+    pub scale: f64,
+    /// Number of reweighted fits.
+    pub iterations: usize,
+}
+
+/// Iteratively reweighted fits, with `w` the weights of the fitter, `weights` setting the weights of a copy of the
+/// fitter, and `smooth` its fit method.
+pub(crate) fn reweighted_fit<F: Clone, const K: usize, const N: usize>(
+    routine: &'static str,
+    fitter: &F,
+    w: &[f64],
+    loss: RobustLoss,
+    n_iter: Option<usize>,
+    weights: impl Fn(F, Vec<f64>) -> FitResult<F>,
+    smooth: impl Fn(F) -> FitResult<FittedCurve<K, N>>,
+) -> FitResult<RobustFit<K, N>> {
+    let n_iter = n_iter.unwrap_or(20);
+    let mut rw = vec![1.0; w.len()];
+    let mut fit = smooth(fitter.clone())?;
+    for iteration in 1..=n_iter {
+        let r: Vec<f64> = fit.report.residuals.chunks(N).map(|r| r.iter().map(|r| r * r).sum::<f64>().sqrt()).collect();
+        let scale = median(&r) / 0.6745;
+        if scale == 0.0 {
+            // exact fit for at least half of the points
+            return Ok(RobustFit { fit, weights: rw, outliers: Vec::new(), scale, iterations: iteration });
+        }
+        let rw_next: Vec<f64> = r.iter().map(|r| loss.weight(r / scale).max(MIN_WEIGHT)).collect();
+        let change = rw.iter().zip(&rw_next).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        rw = rw_next;
+        if iteration > 1 && change < TOL {
+            let outliers = (0..rw.len()).filter(|&i| rw[i] < OUTLIER_WEIGHT).collect();
+            return Ok(RobustFit { fit, weights: rw, outliers, scale, iterations: iteration });
+        }
+        let wi = w.iter().zip(&rw).map(|(w, rw)| w * rw.sqrt()).collect();
+        fit = smooth(weights(fitter.clone(), wi)?)?;
+    }
+    Err(FitError::NotConverged { routine, iterations: n_iter })
+}
+
+fn median(v: &[f64]) -> f64 {
+    let mut v = v.to_vec();
+    v.sort_by(f64::total_cmp);
+    let m = v.len();
+    if m % 2 == 1 {
+        v[m / 2]
+    } else {
+        0.5 * (v[m / 2 - 1] + v[m / 2])
+    }
+}
@@ -0,0 +1,383 @@
+//! Selection of the Smoothing Factor of Smoothing Splines
+//!
+//! The `select_smoothing` methods of `SplineCurveFit`, `ParameterSplineCurveFit`, and `ClosedParameterSplineCurveFit`
+//! fit smoothing splines for a range of target rms values, score each fit by generalized cross-validation (GCV),
+//! leave-one-out cross-validation (LOO), and an L-curve criterion, and return the best fit for a selected
+//! [`Criterion`], together with the table of the scored candidates.
+//!
+//! A Dierckx smoothing spline, with knots `t`, minimizes the sum of the squared jumps of its `k`-th derivative at the
+//! knots, with a sum of squared residuals `fp` equal to the smoothing factor `s`. This is the same spline as the
+//! penalized least squares spline with the same knots, and a penalty weight for which `fp=s`, which is a linear
+//! smoother of the data. To score a candidate, its penalty weight is found by refitting the data in Rust, with the
+//! knots calculated by Dierckx, which gives the leverage values `h(i)` of the data points, as the diagonal elements
+//! of the smoother matrix, and their sum, the effective degrees of freedom `edf` of the fit.
+//! With `m` data points these scores are:
+//!
+//! - GCV: `m * fp / (m - edf)^2`, divided by the number of dimensions `N`,
+//! - LOO: the mean of the squared weighted residuals `(w(i)*r(i)/(1-h(i)))^2`, also divided by `N`,
+//! - L-curve: the curvature of the curve with the logarithm of `fp` and the roughness of the candidates as
+//!   coordinates, with the roughness the sum of the squared jumps of the `k`-th derivative. The selected fit has the
+//!   largest curvature: the corner of the L-curve, where a further decrease of `fp` costs a large increase in
+//!   roughness.
+//!
+//! # Effective Degrees of Freedom
+//!
+//! The same refit gives the effective degrees of freedom, the trace of the smoother matrix, reported as `edf` in the
+//! [`FitReport`] of a smoothing fit. As it takes a series of penalized least squares solves, which is much slower than
+//! the Dierckx fit, it is only done when requested by the `with_edf` method of the fitter; least squares fits always
+//! report their number of free coefficients.
+
+use crate::bspline::{basis, discontinuity_jumps, interval, periodic_discontinuity_jump, MAX_ORDER};
+use crate::lsq::{smoothing_weight, LeastSquares};
+use crate::{FitError, FitReport, FitResult};
+use spliny::SplineCurve;
+
+/// Criterion used by the `select_smoothing` methods to select the best fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// Smallest generalized cross-validation score.
+    Gcv,
+    /// Smallest leave-one-out cross-validation score.
+    LeaveOneOut,
+    /// Largest curvature of the L-curve; this requires at least three candidates.
+    LCurve,
+}
+
+/// Scores of a smoothing spline fit, for a target rms value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothingCandidate {
+    /// Target root mean square error of the fit.
+    pub rms: f64,
+    /// Root mean square error of the fit obtained.
+    pub e_rms: f64,
+    /// Number of knots of the spline.
+    pub knots: usize,
+    /// Effective degrees of freedom, the trace of the smoother matrix.
+    pub edf: f64,
+    /// Generalized cross-validation score.
+    pub gcv: f64,
+    /// Leave-one-out cross-validation score.
+    pub loo: f64,
+    /// Sum of the squared jumps of the `k`-th derivative of the spline.
+    pub roughness: f64,
+    /// Curvature of the L-curve; not available for the candidates at the ends of the L-curve.
+    pub curvature: Option<f64>,
+}
+
+/// Result of a smoothing factor selection: the selected spline, and the scored candidates, in order of the target rms
+/// values supplied.
+#[derive(Debug, Clone)]
+pub struct SmoothingSelection<const K: usize, const N: usize> {
+    pub spline: SplineCurve<K, N>,
+    pub criterion: Criterion,
+    /// Index of the selected candidate.
+    pub selected: usize,
+    pub candidates: Vec<SmoothingCandidate>,
+}
+
+/// Target rms values, for use with the `select_smoothing` methods, logarithmically spaced from `start` to `end`.
+pub fn rms_range(start: f64, end: f64, n: usize) -> Vec<f64> {
+    match n {
+        0 => Vec::new(),
+        1 => vec![start],
+        _ => (0..n).map(|i| start * (end / start).powf(i as f64 / (n - 1) as f64)).collect(),
+    }
+}
+
+/// Data, and constraints, of a curve fit, for scoring the smoothing splines fitted to it, and for their fit reports.
+pub(crate) struct Smoother<'a> {
+    pub(crate) routine: &'static str,
+    pub(crate) u: &'a [f64],
+    pub(crate) xn: &'a [f64], // N coordinates per data point
+    pub(crate) w: &'a [f64],
+    /// Number of coefficients at the begin and end of the spline fixed by end point constraints.
+    pub(crate) fixed: (usize, usize),
+    /// Periodic spline, for which the last data point is not used.
+    pub(crate) periodic: bool,
+    /// Number of interior constraints applied to the spline, each fixing a combination of the free coefficients.
+    pub(crate) constraints: usize,
+}
+
+/// Effective degrees of freedom reported for a fit; see the
+/// [module documentation](self#effective-degrees-of-freedom).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Edf {
+    /// Not available, as for fits with constraints not represented in the smoother matrix.
+    None,
+    /// Least squares fit: the number of free coefficients.
+    LeastSquares,
+    /// Smoothing fit, with its smoother matrix trace calculated if set, and if there are no interior constraints.
+    /// Smoothing fits returned as a least squares spline, with `ier` -1 or -2, report their number of free
+    /// coefficients.
+    Smoothing(bool),
+}
+
+// coefficient of a B-spline in the least squares problem
+enum Column {
+    Free(usize),
+    Fixed(usize),
+}
+
+impl Smoother<'_> {
+    /// Fits smoothing splines with the target rms values, using `fit`, and selects the best one.
+    ///
+    /// Candidates for which the fit fails are left out; an error is returned if none of the fits succeeds.
+    pub(crate) fn select<const K: usize, const N: usize>(
+        &self,
+        rms: &[f64],
+        criterion: Criterion,
+        fit: impl Fn(f64) -> FitResult<SplineCurve<K, N>>,
+    ) -> FitResult<SmoothingSelection<K, N>> {
+        let mut candidates = Vec::with_capacity(rms.len());
+        let mut splines = Vec::with_capacity(rms.len());
+        let mut error = None;
+        for &rms in rms {
+            match fit(rms).map(|s| (self.score::<K, N>(rms, &s), s)) {
+                Ok((Some(c), s)) => {
+                    candidates.push(c);
+                    splines.push(s);
+                }
+                Ok((None, _)) => error = Some(FitError::RankDeficient { routine: self.routine }),
+                Err(e) => error = Some(e),
+            }
+        }
+        if candidates.is_empty() {
+            return Err(error.unwrap_or(FitError::InvalidInput {
+                routine: self.routine,
+                reason: "no target rms values supplied".to_string(),
+            }));
+        }
+        Self::curvatures(&mut candidates);
+
+        let best = |score: &dyn Fn(&SmoothingCandidate) -> Option<f64>| {
+            candidates
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| score(c).filter(|v| v.is_finite()).map(|v| (i, v)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        };
+        let selected = match criterion {
+            Criterion::Gcv => best(&|c| Some(c.gcv)),
+            Criterion::LeaveOneOut => best(&|c| Some(c.loo)),
+            Criterion::LCurve => best(&|c| c.curvature.map(|v| -v)),
+        };
+        let Some(selected) = selected else {
+            return Err(FitError::InvalidInput {
+                routine: self.routine,
+                reason: format!("no candidate could be scored by criterion {criterion:?}"),
+            });
+        };
+        let spline = splines.swap_remove(selected);
+        Ok(SmoothingSelection { spline, criterion, selected, candidates })
+    }
+
+    // number of data points used
+    fn m(&self) -> usize {
+        if self.periodic {
+            self.u.len() - 1
+        } else {
+            self.u.len()
+        }
+    }
+
+    // number of coefficients in the least squares problem, and the mapping of the B-splines to them
+    fn columns(&self, n: usize, k: usize) -> (usize, impl Fn(usize) -> Column + '_) {
+        let nb = n - k - 1;
+        let nc = if self.periodic { n - 2 * k - 1 } else { nb - self.fixed.0 - self.fixed.1 };
+        let column = move |j: usize| {
+            if self.periodic {
+                Column::Free(j % nc)
+            } else if j < self.fixed.0 || j >= nb - self.fixed.1 {
+                Column::Fixed(j)
+            } else {
+                Column::Free(j - self.fixed.0)
+            }
+        };
+        (nc, column)
+    }
+
+    // jumps of the k-th derivative, with the index of the first B-spline, modulo the number of coefficients of
+    // one period for a periodic spline
+    fn jumps(&self, t: &[f64], k: usize) -> Vec<(usize, [f64; MAX_ORDER + 1])> {
+        let mut jumps = discontinuity_jumps(t, k);
+        if self.periodic {
+            jumps.push((t.len() - 2 * k - 2, periodic_discontinuity_jump(t, k)));
+        }
+        jumps
+    }
+
+    /// Fit diagnostics of a spline fitted to the data, with the effective degrees of freedom as selected by `edf`.
+    pub(crate) fn report<const K: usize, const N: usize>(
+        &self,
+        s: &SplineCurve<K, N>,
+        ier: i32,
+        iterations: usize,
+        edf: Edf,
+    ) -> FitReport {
+        let (t, c) = (&s.t, &s.c);
+        let nb = t.len() - K - 1;
+        let residuals: Vec<f64> = self
+            .u
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &u)| {
+                let l = interval(t, K, u);
+                let h = basis(t, K, u, l);
+                (0..N).map(move |d| self.xn[i * N + d] - (0..=K).map(|a| h[a] * c[d * nb + l - K + a]).sum::<f64>())
+            })
+            .collect();
+        // free coefficients, less those fixed by the interior constraints
+        let free = || Some(self.columns(t.len(), K).0.saturating_sub(self.constraints) as f64);
+        let edf = match edf {
+            Edf::LeastSquares => free(),
+            Edf::Smoothing(_) if ier == -1 || ier == -2 => free(),
+            // the smoother matrix does not include the interior constraints
+            Edf::Smoothing(true) if self.constraints == 0 => self.score(0.0, s).map(|c| c.edf),
+            _ => None,
+        };
+        let m = self.m();
+        let fp = residuals.chunks(N).take(m).zip(self.w).map(|(r, w)| w * w * r.iter().map(|r| r * r).sum::<f64>()).sum();
+        FitReport {
+            routine: self.routine,
+            fp,
+            e_rms: (fp / m as f64).sqrt(),
+            residuals,
+            knots: t.len(),
+            ier,
+            edf,
+            iterations,
+        }
+    }
+
+    /// Scores a spline fitted to the data, returning `None` if its penalty weight can not be found.
+    fn score<const K: usize, const N: usize>(&self, rms: f64, s: &SplineCurve<K, N>) -> Option<SmoothingCandidate> {
+        let (t, c) = (&s.t, &s.c);
+        let (n, m) = (t.len(), self.m());
+        let nb = n - K - 1;
+        let (nc, column) = self.columns(n, K);
+        let jumps = self.jumps(t, K);
+        let nper = n - 2 * K - 1; // coefficients per period, for periodic splines
+
+        // observation rows, with the fixed coefficients moved to the right hand sides
+        let mut rows = Vec::with_capacity(m);
+        let mut fp = 0.0;
+        for i in 0..m {
+            let l = interval(t, K, self.u[i]);
+            let h = basis(t, K, self.u[i], l);
+            let mut row = Vec::with_capacity(K + 1);
+            let mut z: [f64; N] = std::array::from_fn(|d| self.xn[i * N + d]);
+            let mut r = z;
+            for (a, &h) in h.iter().enumerate().take(K + 1) {
+                let j = l - K + a;
+                r.iter_mut().enumerate().for_each(|(d, r)| *r -= h * c[d * nb + j]);
+                match column(j) {
+                    Column::Free(col) => row.push((col, h)),
+                    Column::Fixed(j) => z.iter_mut().enumerate().for_each(|(d, z)| *z -= h * c[d * nb + j]),
+                }
+            }
+            fp += self.w[i].powi(2) * r.iter().map(|r| r * r).sum::<f64>();
+            rows.push((row, z, r));
+        }
+        let penalty = jumps
+            .iter()
+            .map(|(i0, jump)| {
+                let mut row = Vec::with_capacity(K + 2);
+                let mut z = [0.0; N];
+                for (a, &v) in jump.iter().enumerate().take(K + 2) {
+                    let j = if self.periodic { (i0 + a) % nper } else { i0 + a };
+                    match column(j) {
+                        Column::Free(col) => row.push((col, v)),
+                        Column::Fixed(j) => z.iter_mut().enumerate().for_each(|(d, z)| *z -= v * c[d * nb + j]),
+                    }
+                }
+                (row, z)
+            })
+            .collect::<Vec<_>>();
+        let roughness: f64 = jumps
+            .iter()
+            .map(|(i0, jump)| {
+                (0..N)
+                    .map(|d| {
+                        let j = |a: usize| if self.periodic { (i0 + a) % nper } else { i0 + a };
+                        (0..K + 2).map(|a| jump[a] * c[d * nb + j(a)]).sum::<f64>().powi(2)
+                    })
+                    .sum::<f64>()
+            })
+            .sum();
+
+        // penalized least squares fit, returning the sum of squared residuals
+        let fit = |lambda: f64| -> Result<(LeastSquares, f64), ()> {
+            let mut lsq = LeastSquares::with_rhs(nc, N, K + 2, if self.periodic { K + 1 } else { 0 });
+            for (i, (row, z, _)) in rows.iter().enumerate() {
+                lsq.add_row_rhs(row.iter().copied(), z, self.w[i]);
+            }
+            if lambda > 0.0 {
+                for (row, z) in &penalty {
+                    lsq.add_row_rhs(row.iter().copied(), z, lambda);
+                }
+            }
+            let x = lsq.solve().ok_or(())?;
+            let fp = rows
+                .iter()
+                .enumerate()
+                .map(|(i, (row, z, _))| {
+                    let r2: f64 = (0..N)
+                        .map(|d| (z[d] - row.iter().map(|&(col, h)| h * x[col * N + d]).sum::<f64>()).powi(2))
+                        .sum();
+                    self.w[i].powi(2) * r2
+                })
+                .sum();
+            Ok((lsq, fp))
+        };
+        let lsq = match fit(0.0) {
+            Ok((lsq, fp0)) if fp <= fp0 * (1.0 + 1E-3) => lsq,
+            Ok(lsq) => smoothing_weight(lsq, fp, fit).ok()?.0,
+            // more coefficients than data points, as for interpolating periodic splines: start with some smoothing
+            Err(()) => {
+                let start = fit(1E-6).ok()?;
+                if start.1 > fp {
+                    return None;
+                }
+                smoothing_weight(start, fp, fit).ok()?.0
+            }
+        };
+
+        let mut edf = 0.0;
+        let mut loo = 0.0;
+        for (i, (row, _, r)) in rows.iter().enumerate() {
+            let h = lsq.leverage(row.iter().map(|&(col, v)| (col, self.w[i] * v)));
+            edf += h;
+            loo += self.w[i].powi(2) * r.iter().map(|r| r * r).sum::<f64>() / (1.0 - h).powi(2);
+        }
+        let (mf, nf) = (m as f64, N as f64);
+        let gcv = if edf < mf { mf * fp / (nf * (mf - edf).powi(2)) } else { f64::INFINITY };
+        Some(SmoothingCandidate {
+            rms,
+            e_rms: (fp / mf).sqrt(),
+            knots: n,
+            edf,
+            gcv,
+            loo: loo / (mf * nf),
+            roughness,
+            curvature: None,
+        })
+    }
+
+    // curvatures of the L-curve, through the points (ln fp, ln roughness), ordered by fp
+    fn curvatures(candidates: &mut [SmoothingCandidate]) {
+        let point = |c: &SmoothingCandidate| [(c.e_rms.powi(2)).ln(), c.roughness.ln()];
+        let mut order: Vec<usize> =
+            (0..candidates.len()).filter(|&i| point(&candidates[i]).iter().all(|v| v.is_finite())).collect();
+        order.sort_by(|&a, &b| candidates[a].e_rms.total_cmp(&candidates[b].e_rms));
+        order.dedup_by(|a, b| point(&candidates[*a]) == point(&candidates[*b]));
+        for w in order.windows(3) {
+            let [p, q, r] = [w[0], w[1], w[2]].map(|i| point(&candidates[i]));
+            // Menger curvature, positive for a convex corner, as for an L-curve
+            let (a, b) = ([q[0] - p[0], q[1] - p[1]], [r[0] - q[0], r[1] - q[1]]);
+            let cross = a[0] * b[1] - a[1] * b[0];
+            let d = |u: [f64; 2], v: [f64; 2]| (u[0] - v[0]).hypot(u[1] - v[1]);
+            candidates[w[1]].curvature = Some(2.0 * cross / (d(p, q) * d(q, r) * d(p, r)));
+        }
+    }
+}
@@ -1,10 +1,15 @@
-use super::Result;
-use csv::ReaderBuilder;
-use serde::Serialize;
+use super::{CsvReader, CsvWriter, FitError, FittedCurve, Result};
+use serde::{Deserialize, Serialize};
 use spliny::SplineCurve;
 
+/// Version of the spline data format written by `SplineCurveData`.
+///
+/// Version 0 is the format without a version field, written by earlier releases, which has the same layout.
+pub const SPLINE_DATA_VERSION: u32 = 1;
+
 #[derive(Serialize)]
 pub struct SplineCurveData<'a> {
+    pub version: u32,
     pub k: usize,
     pub n: usize,
     pub t: &'a [f64],
@@ -13,57 +18,88 @@ pub struct SplineCurveData<'a> {
 
 impl<'a, const K: usize, const N: usize> From<&'a SplineCurve<K, N>> for SplineCurveData<'a> {
     fn from(s: &'a SplineCurve<K, N>) -> Self {
-        Self { k: K, n: N, t: &s.t, c: &s.c }
+        Self { version: SPLINE_DATA_VERSION, k: K, n: N, t: &s.t, c: &s.c }
     }
 }
 
-pub fn read_csv_xy(csv_file: &str) -> Result<(Vec<f64>, Vec<f64>)> {
-    let mut rdr = csv::Reader::from_path(csv_file)?;
-    //let mut r = csv::StringRecord::new();
-    let mut x = Vec::<f64>::new();
-    let mut y = Vec::<f64>::new();
-    for r in rdr.records() {
-        if let Ok(r) = r {
-            x.push(r[0].parse::<f64>().unwrap());
-            y.push(r[1].parse::<f64>().unwrap());
-        } else {
-            break
-        }
+impl<'a, const K: usize, const N: usize> From<&'a FittedCurve<K, N>> for SplineCurveData<'a> {
+    fn from(s: &'a FittedCurve<K, N>) -> Self {
+        Self::from(&s.spline)
     }
-    Ok((x,y))
 }
 
-pub fn read_csv_uxy(csv_file: &str) -> Result<(Vec<f64>, Vec<f64>, Vec<f64>)> {
-    //let mut rdr = csv::Reader::from_path(csv_file)?;
-    let mut rdr = 
-        ReaderBuilder::new()
-        .has_headers(false)
-        .comment(Some(b'#'))
-        .trim(csv::Trim::All)
-        .from_path(csv_file)?;
-    //let mut r = csv::StringRecord::new();
-    let mut u = Vec::<f64>::new();
-    let mut x = Vec::<f64>::new();
-    let mut y = Vec::<f64>::new();
-    for r in rdr.records() {
-        if let Ok(r) = r {
-            u.push(r[0].parse::<f64>().unwrap());
-            x.push(r[1].parse::<f64>().unwrap());
-            y.push(r[2].parse::<f64>().unwrap());
-        } else {
-            break
+/// Owned spline data, as written by `SplineCurveData`, which can be deserialized, and converted into a
+/// `SplineCurve<K,N>` with `try_into`, checking its degree, dimension and sizes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedSplineCurveData {
+    #[serde(default)]
+    pub version: u32,
+    pub k: usize,
+    pub n: usize,
+    pub t: Vec<f64>,
+    pub c: Vec<f64>,
+}
+
+impl<const K: usize, const N: usize> From<&SplineCurve<K, N>> for OwnedSplineCurveData {
+    fn from(s: &SplineCurve<K, N>) -> Self {
+        Self { version: SPLINE_DATA_VERSION, k: K, n: N, t: s.t.clone(), c: s.c.clone() }
+    }
+}
+
+impl From<SplineCurveData<'_>> for OwnedSplineCurveData {
+    fn from(d: SplineCurveData<'_>) -> Self {
+        Self { version: d.version, k: d.k, n: d.n, t: d.t.to_vec(), c: d.c.to_vec() }
+    }
+}
+
+impl<const K: usize, const N: usize> TryFrom<OwnedSplineCurveData> for SplineCurve<K, N> {
+    type Error = FitError;
+
+    fn try_from(d: OwnedSplineCurveData) -> std::result::Result<Self, Self::Error> {
+        let invalid = |reason: String| Err(FitError::InvalidSplineData { reason });
+        if d.version > SPLINE_DATA_VERSION {
+            return invalid(format!("format version {} is not supported, the latest is {SPLINE_DATA_VERSION}", d.version));
         }
+        if d.k != K || d.n != N {
+            return invalid(format!("degree {} and dimension {} do not match the spline's {K} and {N}", d.k, d.n));
+        }
+        if d.t.len() < 2 * (K + 1) {
+            return invalid(format!("{} knots, a spline of degree {K} needs at least {}", d.t.len(), 2 * (K + 1)));
+        }
+        if let Some(i) = d.t.iter().position(|t| !t.is_finite()) {
+            return invalid(format!("knot t[{i}] is not finite"));
+        }
+        if let Some(i) = d.t.windows(2).position(|w| w[1] < w[0]) {
+            return invalid(format!("knot t[{}] is smaller than t[{i}], knots should be non-decreasing", i + 1));
+        }
+        let nc = N * (d.t.len() - K - 1);
+        if d.c.len() != nc {
+            return invalid(format!("{} coefficients, {} knots of a spline of dimension {N} need {nc}", d.c.len(), d.t.len()));
+        }
+        if let Some(i) = d.c.iter().position(|c| !c.is_finite()) {
+            return invalid(format!("coefficient c[{i}] is not finite"));
+        }
+        Ok(SplineCurve::new(d.t, d.c))
     }
-    Ok((u, x,y))
 }
 
+/// Reads x and y values from the first two columns of a CSV file with a header; see [`CsvReader`] for other
+/// layouts.
+///
+/// As in earlier releases, values are not trimmed, and lines starting with `#` are not skipped, but read as data.
+pub fn read_csv_xy(csv_file: &str) -> Result<(Vec<f64>, Vec<f64>)> {
+    Ok(CsvReader::new().comment(None).trim(false).read_xy(csv_file, 0, 1)?)
+}
+
+/// Reads curve parameter values, and x and y values, from the first three columns of a CSV file without header;
+/// see [`CsvReader`] for other layouts.
+pub fn read_csv_uxy(csv_file: &str) -> Result<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+    let mut data = CsvReader::new().has_headers(false).read(csv_file, &[0.into(), 1.into(), 2.into()])?.into_iter();
+    Ok((data.next().unwrap(), data.next().unwrap(), data.next().unwrap()))
+}
+
+/// Writes spectral x and y values, with headers `wl[nm]` and `spd[-]`, and 2 and 4 decimals; see [`CsvWriter`] for
+/// other formats, and for writing sampled splines.
 pub fn write_csv_xy(csv_file: &str, x: &[f64], y: &[f64]) -> Result<()> {
-    let mut wtr = csv::Writer::from_path(csv_file)?;
-    wtr.write_record(&["wl[nm]", "spd[-]"])?;
-    for (&x,&y) in x.iter().zip(y.iter()) {
-        
-        wtr.write_record(&[format!("{:.2}",x), format!("{:.4}",y)])?
-    }
-    wtr.flush()?;
-    Ok(())
-}
\ No newline at end of file
+    Ok(CsvWriter::new().headers(["wl[nm]", "spd[-]"]).precisions(vec![2, 4]).write(csv_file, &[x, y])?)
+}
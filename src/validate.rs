@@ -0,0 +1,129 @@
+//! Input checks, used by the fitter constructors and weight setters.
+//!
+//! The Dierckx routines check part of their input themselves, returning `ier=10` without telling which value is
+//! wrong, but do not check for values which are not finite, which results in undefined behavior. These checks run
+//! before any data is passed to a Dierckx routine, and report the location of the first offending value.
+
+use super::{FitError, FitResult};
+
+/// Checks if all values are finite, and returns the index of the first value which is not.
+pub(crate) fn finite(routine: &'static str, what: &'static str, v: &[f64]) -> FitResult<()> {
+    match v.iter().position(|v| !v.is_finite()) {
+        Some(index) => Err(FitError::NonFinite { routine, what, index }),
+        None => Ok(()),
+    }
+}
+
+/// Checks if the values are finite, and in strictly ascending order.
+pub(crate) fn strictly_ascending(routine: &'static str, what: &'static str, v: &[f64]) -> FitResult<()> {
+    finite(routine, what, v)?;
+    match v.windows(2).position(|w| w[1] <= w[0]) {
+        Some(i) => Err(FitError::NotAscending { routine, what, index: i + 1 }),
+        None => Ok(()),
+    }
+}
+
+/// Checks the number of weights, and if all weights are finite and larger than zero.
+pub(crate) fn weights(routine: &'static str, w: &[f64], m: usize) -> FitResult<()> {
+    size(routine, "weights", w, m)?;
+    finite(routine, "weights", w)?;
+    match w.iter().position(|&w| w <= 0.0) {
+        Some(index) => Err(FitError::NonPositiveWeight { routine, index, value: w[index] }),
+        None => Ok(()),
+    }
+}
+
+/// Checks the size of an input array.
+pub(crate) fn size(routine: &'static str, what: &'static str, v: &[f64], expected: usize) -> FitResult<()> {
+    if v.len() == expected {
+        Ok(())
+    } else {
+        Err(FitError::SizeMismatch { routine, what, expected, found: v.len() })
+    }
+}
+
+/// Checks interior knots: finite, strictly ascending, and within the open range `(ub, ue)` of the data.
+pub(crate) fn interior_knots(routine: &'static str, knots: &[f64], ub: f64, ue: f64) -> FitResult<()> {
+    strictly_ascending(routine, "knots", knots)?;
+    match knots.iter().find(|&&t| t <= ub || t >= ue) {
+        Some(&value) => Err(FitError::OutOfRange { routine, value, range: [ub, ue] }),
+        None => Ok(()),
+    }
+}
+
+/// Checks the Schoenberg-Whitney conditions for the knots `t` of a spline of degree `k`, and data parameter values `u`
+/// in ascending order, as Dierckx' `fpchec`: there should be a subset of data points `u[i0] < u[i1] < ...`, with
+/// `u[ij]` inside the support `t[j]..t[j+k+1]` of B-spline `j`, for all B-splines.
+pub(crate) fn schoenberg_whitney(routine: &'static str, t: &[f64], k: usize, u: &[f64]) -> FitResult<()> {
+    let (n, m) = (t.len(), u.len());
+    let nc = n - k - 1;
+    let error = |j: usize| FitError::SchoenbergWhitney { routine, index: j, interval: [t[j], t[j + k + 1]] };
+    if m == 0 || u[0] >= t[k + 1] {
+        return Err(error(0));
+    }
+    if u[m - 1] <= t[n - k - 2] {
+        return Err(error(nc - 1));
+    }
+    let mut i = 0;
+    for j in 1..nc - 1 {
+        loop {
+            i += 1;
+            if i >= m - 1 {
+                return Err(error(j));
+            }
+            if u[i] > t[j] {
+                break;
+            }
+        }
+        if u[i] >= t[j + k + 1] {
+            return Err(error(j));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the Schoenberg-Whitney conditions for the knots `t` of a periodic spline of degree `k`, with period
+/// `t[n-k-1]-t[k]`, and data parameter values `u` in ascending order, including the last point, which closes the
+/// period, as Dierckx' `fpchep`: there should be a subset of data points, taken cyclically, starting at one of the
+/// first points, with a point inside the support `t[j]..t[j+k+1]` of each of the B-splines `j = k..n-k-1`.
+///
+/// If there is no such subset, the error reports the first B-spline without data for the subset which covers the
+/// most B-splines.
+pub(crate) fn periodic_schoenberg_whitney(routine: &'static str, t: &[f64], k: usize, u: &[f64]) -> FitResult<()> {
+    let (n, m) = (t.len(), u.len());
+    let per = t[n - k - 1] - t[k];
+    // data point `c`, with the points of the next period following the last point of this period
+    let x = |c: usize| if c < m - 1 { u[c] } else { u[c + 1 - m] + per };
+    // first B-spline without a data point in its support, for a subset of the points starting at point `s`
+    let first_without_data = |s: usize| {
+        let mut c = s;
+        (k..n - k - 1).find(|&j| {
+            while c < s + m - 1 && x(c) <= t[j] {
+                c += 1;
+            }
+            let found = c < s + m - 1 && x(c) < t[j + k + 1];
+            c += 1;
+            !found
+        })
+    };
+    // as fpchep, start the subsets at the points before the one beyond the first k+1 interior knots
+    let mut l = m;
+    let mut l1 = k + 1;
+    'points: for (i, &xi) in u.iter().enumerate() {
+        while xi >= t[l1] && i + 1 != n - k - 1 {
+            l1 += 1;
+            if l1 > 2 * k + 1 {
+                l = i + 1;
+                break 'points;
+            }
+        }
+    }
+    let mut index = k;
+    for s in 1..l {
+        match first_without_data(s) {
+            None => return Ok(()),
+            Some(j) => index = index.max(j),
+        }
+    }
+    Err(FitError::SchoenbergWhitney { routine, index, interval: [t[index], t[index + k + 1]] })
+}
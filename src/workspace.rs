@@ -0,0 +1,75 @@
+//! Reusable Work Space for Repeated Fits
+//!
+//! The fitters allocate their knot, coefficient and work arrays for the worst case, which for the smoothing fits is
+//! much larger than the spline returned. When fitting many small data sets, such as spectra, these allocations
+//! dominate the fit time. A [`FitWorkspace`] keeps these arrays, and the weights array, between fits: the
+//! `with_workspace` constructors of `SplineCurveFit` and `ParameterSplineCurveFit` take the arrays from the
+//! workspace, and the fitter returns them when it is dropped, after the fit, or when the fit fails, so the next
+//! fitter created with the same workspace does not need to allocate them again, as long as its data set is not
+//! larger. The spline, and the residuals of its fit report, are returned to the caller, and are allocated for each
+//! fit.
+//!
+//! The workspace is a handle, which can be cloned, and shared between threads; each fitter takes the arrays for its
+//! own use, and a fitter created while the arrays are in use allocates new ones. Clones of a fitter, as made by the
+//! `select_smoothing` and robust fit methods, have their own arrays, and do not return them to the workspace.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Knot, coefficient and work arrays of a fitter.
+#[derive(Debug, Default)]
+pub(crate) struct Buffers {
+    pub(crate) t: Vec<f64>,
+    pub(crate) c: Vec<f64>,
+    pub(crate) wrk: Vec<f64>,
+    pub(crate) iwrk: Vec<i32>,
+    pub(crate) xx: Vec<f64>,
+    pub(crate) cp: Vec<f64>,
+    pub(crate) w: Vec<f64>,
+}
+
+/// Work space, to be reused by a sequence of fits; see the [`workspace`](crate::workspace) module.
+#[derive(Debug, Clone, Default)]
+pub struct FitWorkspace(Arc<Mutex<Buffers>>);
+
+impl FitWorkspace {
+    /// Empty work space; the arrays are allocated by the first fit using it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total number of values, of all arrays, kept in the work space.
+    pub fn capacity(&self) -> usize {
+        let b = self.lock();
+        b.t.capacity() + b.c.capacity() + b.wrk.capacity() + b.iwrk.capacity() + b.xx.capacity() + b.cp.capacity()
+            + b.w.capacity()
+    }
+
+    // takes the arrays, leaving the workspace empty
+    pub(crate) fn take(&self) -> Buffers {
+        std::mem::take(&mut *self.lock())
+    }
+
+    // returns arrays to the workspace, keeping the largest
+    pub(crate) fn give(&self, buffers: Buffers) {
+        let mut b = self.lock();
+        if buffers.wrk.capacity() >= b.wrk.capacity() {
+            *b = buffers;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Buffers> {
+        // the buffers are always in a valid state, also after a panic of another thread
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Array `v`, with `n` zero values, reallocated only if its capacity is less than `n`. The values of a previous fit
+/// are reset, as a continued fit (`iopt=1`) reads its work arrays, and should not see those of another fit.
+pub(crate) fn sized<T: Copy + Default>(mut v: Vec<T>, n: usize) -> Vec<T> {
+    if v.capacity() < n {
+        return vec![T::default(); n];
+    }
+    v.clear();
+    v.resize(n, T::default());
+    v
+}
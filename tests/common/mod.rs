@@ -0,0 +1,83 @@
+//! Synthetic data sets, shared by the integration tests.
+#![allow(dead_code)]
+
+use std::f64::consts::TAU;
+
+/// `n + 1` values `i / d`, starting at zero.
+pub fn grid(n: usize, d: f64) -> Vec<f64> {
+    (0..=n).map(|i| i as f64 / d).collect()
+}
+
+/// Third degree polynomial on [0, 2], which is reproduced exactly by an interpolating cubic spline.
+pub fn cubic() -> (Vec<f64>, Vec<f64>) {
+    let x = grid(20, 10.0);
+    let y = x.iter().map(|x| x.powi(3)).collect();
+    (x, y)
+}
+
+/// Curve `(u², u³)` on [0, 2], with the coordinates of each point stored consecutively.
+pub fn cubic_curve() -> (Vec<f64>, Vec<f64>) {
+    let u = grid(20, 10.0);
+    let xy = u.iter().flat_map(|&u| [u * u, u.powi(3)]).collect();
+    (u, xy)
+}
+
+/// Sine on [0, 4π], sampled in 5 degree steps.
+pub fn sine() -> (Vec<f64>, Vec<f64>) {
+    let x: Vec<f64> = (0..=720).step_by(5).map(|i| (i as f64).to_radians()).collect();
+    let y = x.iter().map(|x| x.sin()).collect();
+    (x, y)
+}
+
+/// One period of `sin x + 0.5 cos 2x`, sampled in 5 degree steps.
+pub fn periodic_data() -> (Vec<f64>, Vec<f64>) {
+    let x: Vec<f64> = grid(72, 72.0).into_iter().map(|x| x * TAU).collect();
+    let y = x.iter().map(|x| x.sin() + 0.5 * (2.0 * x).cos()).collect();
+    (x, y)
+}
+
+/// `n + 1` points on an ellipse with semi-axes `a` and `b`, in angle steps of `step` degrees, with the coordinates of
+/// each point stored consecutively.
+pub fn ellipse(n: usize, step: f64, a: f64, b: f64) -> Vec<f64> {
+    (0..=n)
+        .flat_map(|i| {
+            let phi = (step * i as f64).to_radians();
+            [a * phi.cos(), b * phi.sin()]
+        })
+        .collect()
+}
+
+/// Parabola `x²` on [0, 1], with a ripple, which makes its interpolating spline not convex.
+pub fn wiggly_parabola() -> (Vec<f64>, Vec<f64>) {
+    let x = grid(100, 100.0);
+    let y = x.iter().map(|x| x * x + 0.02 * (40.0 * x).sin()).collect();
+    (x, y)
+}
+
+/// Sine `sin(f x)`, with a deterministic pseudo random noise of amplitude `noise`, with an rms value of about
+/// `0.3 noise`.
+pub fn noisy_sine(x: &[f64], f: f64, noise: f64) -> Vec<f64> {
+    x.iter().enumerate().map(|(i, x)| (f * x).sin() + noise * ((i * 7919 % 13) as f64 / 12.0 - 0.5)).collect()
+}
+
+/// Logistic cumulative distribution on [0, 10], with a non-monotone measurement error.
+pub fn cdf() -> (Vec<f64>, Vec<f64>) {
+    let x = grid(100, 10.0);
+    let y = x.iter().map(|&x| 1.0 / (1.0 + (-(x - 5.0) * 1.5).exp()) + 0.02 * (x * 7.0).sin()).collect();
+    (x, y)
+}
+
+/// Path `(u, sin 1.3u)` on [0, 6], with a ripple, with the coordinates of each point stored consecutively.
+pub fn path() -> (Vec<f64>, Vec<f64>) {
+    let u = grid(60, 10.0);
+    let xy = u.iter().flat_map(|&u| [u, (u * 1.3).sin() + 0.05 * (u * 11.0).cos()]).collect();
+    (u, xy)
+}
+
+/// Spectrum from 380 to 780nm, in steps of `step` nm, with a gaussian peak at `peak`, and a ripple of amplitude
+/// `ripple` as measurement noise.
+pub fn spectrum(step: usize, peak: f64, width: f64, ripple: f64) -> (Vec<f64>, Vec<f64>) {
+    let x: Vec<f64> = (380..=780).step_by(step).map(f64::from).collect();
+    let y = x.iter().map(|x| (-((x - peak) / width).powi(2)).exp() + ripple * (x * 0.7).sin()).collect();
+    (x, y)
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "rayon")]
+mod common;
+
+use splinify::{CubicSplineFit, Dataset, FitError, Result};
+
+// spectra with peaks shifted by 1nm
+fn spectrum(i: usize) -> (Vec<f64>, Vec<f64>) {
+    common::spectrum(1, 440.0 + i as f64, 12.0, 0.002)
+}
+
+#[test]
+fn smoothing_spline_batch() -> Result<()> {
+    let mut datasets: Vec<Dataset> = (0..64).map(|i| {
+        let (x, y) = spectrum(i);
+        let w = (i % 2 == 0).then(|| vec![2.0; x.len()]);
+        (x, y, w)
+    }).collect();
+    datasets[5].0[10] = datasets[5].0[9]; // not ascending
+
+    let results = CubicSplineFit::smoothing_spline_batch(datasets.clone(), 0.005);
+    assert_eq!(results.len(), 64);
+    assert!(matches!(results[5], Err(FitError::NotAscending { index: 10, .. })));
+
+    // same results as sequential fits, in the same order
+    for (i, (r, (x, y, w))) in results.iter().zip(datasets).enumerate() {
+        if i == 5 {
+            continue;
+        }
+        let fit = CubicSplineFit::new(x, y)?;
+        let s = match w {
+            Some(w) => fit.set_weights(w)?.smoothing_spline(0.005)?,
+            None => fit.smoothing_spline(0.005)?,
+        };
+        let r = r.as_ref().unwrap();
+        assert_eq!(r.t, s.t);
+        assert_eq!(r.c, s.c);
+    }
+    Ok(())
+}
+
+#[test]
+fn batch_fit_reports() {
+    let datasets: Vec<Dataset> = (0..8).map(|i| { let (x, y) = spectrum(i); (x, y, None) }).collect();
+    let results = CubicSplineFit::batch(datasets, |fit| fit.cardinal_spline(10.0));
+    assert!(results.iter().all(|r| r.as_ref().is_ok_and(|s| s.report.ier <= 0)));
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use splinify::{ConvexSplineCurveFit, Result};
+use splinify::dierckx_val::evaluate_derivative;
+
+#[test]
+fn convex_smoothing_spline() -> Result<()> {
+    let (x, y) = common::wiggly_parabola();
+    let v = vec![-1.0; x.len()];
+    let s = ConvexSplineCurveFit::new(x.clone(), y, v)?.smoothing_spline(0.01)?;
+    assert_eq!(s.report.routine, "concon");
+    let d2y = evaluate_derivative(&s, &x, 2)?;
+    d2y.iter().for_each(|d2y| assert!(*d2y >= -1E-8));
+    Ok(())
+}
+
+#[test]
+fn concave_continued_fits() -> Result<()> {
+    let (x, y) = common::wiggly_parabola();
+    let y: Vec<f64> = y.iter().map(|y| -y).collect();
+    let v = vec![1.0; x.len()];
+    let mut fit = ConvexSplineCurveFit::new(x.clone(), y, v)?;
+    let mut rms_prev = f64::MAX;
+    for rms in [0.1, 0.05, 0.02, 0.015] {
+        let s = fit.smoothing_spline_continued(rms)?;
+        let d2y = evaluate_derivative(&s, &x, 2)?;
+        d2y.iter().for_each(|d2y| assert!(*d2y <= 1E-8));
+        let e_rms = fit.e_rms().unwrap();
+        assert!(e_rms <= rms_prev);
+        rms_prev = e_rms;
+    }
+    Ok(())
+}
+
+#[test]
+fn constraint_size_mismatch() {
+    let (x, y) = common::wiggly_parabola();
+    assert!(ConvexSplineCurveFit::new(x, y, vec![1.0; 10]).is_err());
+}
@@ -0,0 +1,104 @@
+use splinify::{read_csv_uxy, read_csv_xy, write_csv_xy, Column, CsvError, CsvReader, CsvWriter, FitError};
+use spliny::SplineCurve;
+
+const SPECTRUM: &str = "\
+# LED spectrum
+wl, spd, weight
+380, 0.10, 1.0
+381, 0.12, 2.0
+382, , 1.0
+383, 0.15, 0.5
+";
+
+#[test]
+fn select_columns() -> Result<(), CsvError> {
+    let rdr = CsvReader::new().skip_missing(true);
+    let data = rdr.read_from(SPECTRUM.as_bytes(), &["spd".into(), Column::Index(0)])?;
+    assert_eq!(data, vec![vec![0.10, 0.12, 0.15], vec![380.0, 381.0, 383.0]]);
+
+    let data = CsvReader::new().read_from(SPECTRUM.as_bytes(), &[0.into(), 2.into()])?;
+    assert_eq!(data[1], vec![1.0, 2.0, 1.0, 0.5]);
+    Ok(())
+}
+
+#[test]
+fn csv_errors() {
+    let e = CsvReader::new().read_from(SPECTRUM.as_bytes(), &["wl".into(), "spd".into()]).unwrap_err();
+    assert!(matches!(e, CsvError::Missing { line: 5, column: 1 }));
+
+    let e = CsvReader::new().read_from(SPECTRUM.as_bytes(), &["irradiance".into()]).unwrap_err();
+    assert!(matches!(e, CsvError::UnknownColumn { ref name } if name == "irradiance"));
+
+    let e = CsvReader::new().has_headers(false).read_from(SPECTRUM.as_bytes(), &[0.into()]).unwrap_err();
+    assert!(matches!(e, CsvError::Parse { line: 2, column: 0, ref value } if value == "wl"));
+
+    let e = CsvReader::new().trim(false).read_from(SPECTRUM.as_bytes(), &[0.into(), 1.into()]).unwrap_err();
+    assert!(matches!(e, CsvError::Parse { line: 3, column: 1, ref value } if value == " 0.10"));
+
+    let e = CsvReader::new().read_from("x,y\n1,2\n3\n".as_bytes(), &[0.into(), 1.into()]).unwrap_err();
+    assert!(matches!(e, CsvError::Missing { line: 3, column: 1 }));
+    assert_eq!(e.to_string(), "csv: line 3, column 1: missing value");
+
+    assert!(matches!(CsvReader::new().read("tests/data/no-such-file.csv", &[0.into()]), Err(CsvError::Csv(_))));
+}
+
+#[test]
+fn files() -> splinify::Result<()> {
+    let path = std::env::temp_dir().join("splinify-test-csv-parametric.csv");
+    std::fs::write(&path, "t;x;y;z\n0;1;2;3\n1;4;5;6\n")?;
+    let (u, xn) = CsvReader::new().delimiter(b';').read_parametric(&path, "t", ["x".into(), "y".into(), "z".into()])?;
+    assert_eq!(u, vec![0.0, 1.0]);
+    assert_eq!(xn, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    std::fs::remove_file(&path)?;
+
+    let (x, y) = read_csv_xy("tests/data/leds4000.csv")?;
+    assert_eq!(x.len(), y.len());
+    assert!(x.len() > 100);
+    assert!(read_csv_uxy("tests/data/no-such-file.csv").is_err());
+
+    let path = std::env::temp_dir().join("splinify-test-csv-spectrum.csv");
+    write_csv_xy(path.to_str().unwrap(), &[380.0, 381.5], &[0.1, 0.12345])?;
+    assert_eq!(std::fs::read_to_string(&path)?, "wl[nm],spd[-]\n380.00,0.1000\n381.50,0.1235\n");
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn write_columns() -> Result<(), CsvError> {
+    let mut out = Vec::new();
+    let wtr = CsvWriter::new().headers(["x", "y"]).precisions(vec![1, 3]).delimiter(b'\t');
+    wtr.write_to(&mut out, &[&[1.0, 2.25], &[0.5, 1.0 / 3.0]])?;
+    assert_eq!(String::from_utf8(out).unwrap(), "x\ty\n1.0\t0.500\n2.2\t0.333\n");
+
+    let mut out = Vec::new();
+    CsvWriter::new().write_to(&mut out, &[&[0.1, 2.0], &[1e-20, -3.5]])?;
+    let data = CsvReader::new().has_headers(false).read_from(&out[..], &[0.into(), 1.into()])?;
+    assert_eq!(data, vec![vec![0.1, 2.0], vec![1e-20, -3.5]]);
+
+    let e = CsvWriter::new().write_to(Vec::new(), &[&[1.0, 2.0], &[1.0]]).unwrap_err();
+    assert!(matches!(e, CsvError::ColumnLength { column: 1, expected: 2, found: 1 }));
+    let e = CsvWriter::new().headers(["x"]).write_to(Vec::new(), &[&[1.0], &[1.0]]).unwrap_err();
+    assert!(matches!(e, CsvError::Headers { expected: 2, found: 1 }));
+    Ok(())
+}
+
+#[test]
+fn write_spline() -> splinify::Result<()> {
+    // linear spline through (0, 0, 1), (1, 2, 1), (2, 2, 3)
+    let s = SplineCurve::<1, 2>::new(vec![0.0, 0.0, 1.0, 2.0, 2.0], vec![0.0, 2.0, 2.0, 1.0, 1.0, 3.0]);
+    let mut out = Vec::new();
+    CsvWriter::new().write_spline_to(&mut out, &s, &[0.0, 0.5, 1.5], 1)?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        "u,x0,x1,d1x0,d1x1\n0,0,1,2,0\n0.5,1,1,2,0\n1.5,2,2,0,2\n"
+    );
+
+    let s = SplineCurve::<1, 1>::new(vec![0.0, 0.0, 1.0, 1.0], vec![1.0, 3.0]);
+    let mut out = Vec::new();
+    CsvWriter::new().precision(2).write_spline_to(&mut out, &s, &[0.0, 0.25], 0)?;
+    assert_eq!(String::from_utf8(out)?, "x,y\n0.00,1.00\n0.25,1.50\n");
+
+    let e = CsvWriter::new().write_spline_to(Vec::new(), &s, &[0.0, 0.25], 2).unwrap_err();
+    assert!(matches!(e, CsvError::Fit(FitError::DerivativeOrder { order: 2, k: 1, .. })));
+    Ok(())
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{CubicSplineFit, CubicSplineFit2D, Result};
+use splinify::dierckx_val::{all_derivatives, evaluate, evaluate_derivative, integrate, roots};
+
+#[test]
+fn derivatives_of_cubic() -> Result<()> {
+    // an interpolating cubic spline reproduces a third degree polynomial exactly
+    let (x, y) = common::cubic();
+    let s = CubicSplineFit::new(x.clone(), y)?.interpolating_spline()?;
+
+    let xt = [0.25, 0.5, 1.25, 1.75];
+    let dy = evaluate_derivative(&s, &xt, 1)?;
+    let d2y = evaluate_derivative(&s, &xt, 2)?;
+    let d3y = evaluate_derivative(&s, &xt, 3)?;
+    for (i, x) in xt.iter().enumerate() {
+        assert_abs_diff_eq!(dy[i], 3.0 * x * x, epsilon = 1E-8);
+        assert_abs_diff_eq!(d2y[i], 6.0 * x, epsilon = 1E-8);
+        assert_abs_diff_eq!(d3y[i], 6.0, epsilon = 1E-6);
+    }
+
+    assert!(evaluate_derivative(&s, &xt, 4).is_err());
+    Ok(())
+}
+
+#[test]
+fn derivatives_of_2d_curve() -> Result<()> {
+    let (u, xy) = common::cubic_curve();
+    let s = CubicSplineFit2D::new(u, xy)?.interpolating_spline()?;
+
+    let ut = [0.5, 1.5];
+    let dxy = evaluate_derivative(&s, &ut, 1)?;
+    for (i, u) in ut.iter().enumerate() {
+        assert_abs_diff_eq!(dxy[2 * i], 2.0 * u, epsilon = 1E-8);
+        assert_abs_diff_eq!(dxy[2 * i + 1], 3.0 * u * u, epsilon = 1E-8);
+    }
+    Ok(())
+}
+
+#[test]
+fn evaluate_2d_curve() -> Result<()> {
+    // curev expects the coefficients of each dimension at a stride of n knots, not packed
+    let (u, xy) = common::cubic_curve();
+    let s = CubicSplineFit2D::new(u, xy)?.interpolating_spline()?;
+
+    let ut = [0.25, 0.5, 1.25, 1.75];
+    let xy = evaluate(&s, &ut)?;
+    for (i, u) in ut.iter().enumerate() {
+        assert_abs_diff_eq!(xy[2 * i], u * u, epsilon = 1E-8);
+        assert_abs_diff_eq!(xy[2 * i + 1], u.powi(3), epsilon = 1E-8);
+    }
+    Ok(())
+}
+
+#[test]
+fn integral_of_cubic() -> Result<()> {
+    let (x, y) = common::cubic();
+    let s = CubicSplineFit::new(x, y)?.interpolating_spline()?;
+
+    assert_abs_diff_eq!(integrate(&s, 0.5, 1.5)?, (1.5f64.powi(4) - 0.5f64.powi(4)) / 4.0, epsilon = 1E-10);
+    assert_abs_diff_eq!(integrate(&s, 1.5, 0.5)?, -(1.5f64.powi(4) - 0.5f64.powi(4)) / 4.0, epsilon = 1E-10);
+
+    // the spline is zero outside its knot range [0, 2]
+    assert_abs_diff_eq!(integrate(&s, -1.0, 3.0)?, 4.0, epsilon = 1E-10);
+    assert_abs_diff_eq!(integrate(&s, 2.5, 3.0)?, 0.0, epsilon = 1E-10);
+    assert!(integrate(&s, 0.0, f64::INFINITY).is_err());
+    Ok(())
+}
+
+#[test]
+fn roots_of_sine() -> Result<()> {
+    let (x, y) = common::sine();
+    let s = CubicSplineFit::new(x, y)?.interpolating_spline()?;
+
+    let r = roots(&s, 0.5)?;
+    let expected = [30.0f64, 150.0, 390.0, 510.0];
+    assert_eq!(r.len(), expected.len());
+    for (r, e) in r.iter().zip(expected.iter()) {
+        assert_abs_diff_eq!(*r, e.to_radians(), epsilon = 1E-4);
+    }
+
+    let s1 = splinify::LinearSplineFit::new(vec![0.0, 1.0, 2.0], vec![0.0, 1.0, 0.0])?.interpolating_spline()?;
+    assert!(roots(&s1, 0.5).is_err());
+    Ok(())
+}
+
+#[test]
+fn all_derivatives_at_a_point() -> Result<()> {
+    let (x, y) = common::cubic();
+    let s = CubicSplineFit::new(x, y)?.interpolating_spline()?;
+    let d = all_derivatives(&s, 1.25)?;
+    assert_eq!(d.len(), 4);
+    for (d, e) in d.iter().zip([1.25f64.powi(3), 3.0 * 1.25 * 1.25, 6.0 * 1.25, 6.0]) {
+        assert_abs_diff_eq!(d[0], e, epsilon = 1E-6);
+    }
+    assert!(all_derivatives(&s, 2.5).is_err());
+
+    let (u, xy) = common::cubic_curve();
+    let s2 = CubicSplineFit2D::new(u, xy)?.interpolating_spline()?;
+    let d = all_derivatives(&s2, 0.5)?;
+    assert_abs_diff_eq!(d[1][0], 1.0, epsilon = 1E-8);
+    assert_abs_diff_eq!(d[1][1], 0.75, epsilon = 1E-8);
+    assert_abs_diff_eq!(d[2][0], 2.0, epsilon = 1E-6);
+    assert_abs_diff_eq!(d[2][1], 3.0, epsilon = 1E-6);
+    Ok(())
+}
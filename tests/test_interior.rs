@@ -0,0 +1,75 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::dierckx_val::{evaluate, evaluate_derivative};
+use splinify::{FitError, ParameterSplineCurveFit, Result};
+
+#[test]
+fn way_points_and_tangents() -> Result<()> {
+    let (u, xy) = common::path();
+    let s = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?
+        .interior_constraints(vec![(2.0, 0, [2.0, 0.5]), (2.0, 1, [1.0, 0.0]), (4.5, 0, [4.5, -0.5])])?
+        .smoothing_spline(0.01)?;
+
+    let p = evaluate(&s, &[2.0, 4.5])?;
+    assert_abs_diff_eq!(p[..], [2.0, 0.5, 4.5, -0.5], epsilon = 1E-8);
+    let d = evaluate_derivative(&s, &[2.0], 1)?;
+    assert_abs_diff_eq!(d[..], [1.0, 0.0], epsilon = 1E-8);
+    assert_eq!(s.report.residuals.len(), xy.len());
+
+    // end point constraints remain satisfied
+    let s = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?
+        .begin_constraints([[0.0, 0.0], [1.0, 1.0]])?
+        .interior_constraints(vec![(3.0, 0, [3.0, 0.0])])?
+        .least_squares_spline(vec![1.0, 2.0, 2.5, 3.5, 4.0, 5.0])?;
+    let p = evaluate(&s, &[0.0, 3.0])?;
+    assert_abs_diff_eq!(p[..], [0.0, 0.0, 3.0, 0.0], epsilon = 1E-8);
+    // 10 coefficients, less one fixed by the begin point, and one by the interior constraint
+    assert_eq!(s.report.edf, Some(8.0));
+    Ok(())
+}
+
+#[test]
+fn smoothing_fit_with_constraints() -> Result<()> {
+    // a way point on the data keeps the smoothing fit at its target rms value
+    let (u, xy) = common::path();
+    let s = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?
+        .interior_constraints(vec![(3.0, 0, [xy[60], xy[61]])])?
+        .smoothing_spline(0.04)?;
+    let p = evaluate(&s, &[3.0])?;
+    assert_abs_diff_eq!(p[..], xy[60..62], epsilon = 1E-8);
+    assert_abs_diff_eq!(s.report.e_rms, 0.04, epsilon = 1E-4);
+    Ok(())
+}
+
+#[test]
+fn interpolating_curve_with_constraints() -> Result<()> {
+    // with two derivative constraints at the begin point, the interpolating curve has more coefficients than data
+    // points, and the constraint is met without changing the values at the data points
+    let (u, xy) = common::path();
+    let s = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?
+        .begin_constraints([[xy[0], xy[1]], [1.0, 1.3], [0.0, 0.0]])?
+        .interior_constraints(vec![(2.05, 0, [2.05, 0.6])])?
+        .interpolating_spline()?;
+    let p = evaluate(&s, &[2.05])?;
+    assert_abs_diff_eq!(p[..], [2.05, 0.6], epsilon = 1E-8);
+    assert!(s.report.fp < 1E-10);
+    Ok(())
+}
+
+#[test]
+fn invalid_interior_constraints() -> Result<()> {
+    let (u, xy) = common::path();
+    let fit = ParameterSplineCurveFit::<3, 2>::new(u, xy)?;
+    assert!(matches!(
+        fit.clone().interior_constraints(vec![(7.0, 0, [0.0, 0.0])]),
+        Err(FitError::OutOfRange { routine: "concur", value: 7.0, .. })
+    ));
+    assert!(matches!(
+        fit.clone().interior_constraints(vec![(1.0, 4, [0.0, 0.0])]),
+        Err(FitError::DerivativeOrder { order: 4, k: 3, .. })
+    ));
+    let fit = fit.interior_constraints(vec![(1.0, 0, [0.0, 0.0]), (1.0, 0, [1.0, 0.0])])?;
+    assert!(matches!(fit.smoothing_spline(0.01), Err(FitError::ConstraintsNotSatisfied { .. })));
+    Ok(())
+}
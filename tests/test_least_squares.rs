@@ -0,0 +1,63 @@
+use approx::assert_abs_diff_eq;
+use splinify::{
+    ClosedCubicSplineFit2D, ClosedParameterSplineCurveFit, CubicSplineFit, FitError, ParameterSplineCurveFit, Result,
+};
+
+#[test]
+fn least_squares_curfit() -> Result<()> {
+    let x: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+    let y: Vec<f64> = x.iter().map(|x| x * x * x - 4.0 * x).collect();
+
+    // a cubic polynomial is reproduced exactly, for any knots
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.least_squares_spline(vec![2.5, 3.0, 7.2])?;
+    assert_eq!(s.t.len(), 11);
+    assert_eq!(s.t[..4], [0.0; 4]);
+    assert_eq!(s.t[4..7], [2.5, 3.0, 7.2]);
+    assert_abs_diff_eq!(s.report.fp, 0.0, epsilon = 1E-8);
+
+    // no data point between 5.1 and 5.5 for the B-spline with coefficient 4
+    let e = CubicSplineFit::new(x.clone(), y.clone())?.least_squares_spline(vec![5.1, 5.2, 5.3, 5.4, 5.5]).unwrap_err();
+    assert_eq!(e, FitError::SchoenbergWhitney { routine: "curfit", index: 4, interval: [5.1, 5.5] });
+
+    let e = CubicSplineFit::new(x.clone(), y.clone())?.least_squares_spline(vec![3.0, 2.0]).unwrap_err();
+    assert!(matches!(e, FitError::NotAscending { what: "knots", index: 1, .. }));
+    let e = CubicSplineFit::new(x, y)?.least_squares_spline(vec![3.0, 10.0]).unwrap_err();
+    assert!(matches!(e, FitError::OutOfRange { value: 10.0, .. }));
+    Ok(())
+}
+
+#[test]
+fn least_squares_concur_and_clocur() -> Result<()> {
+    let m = 41;
+    let u: Vec<f64> = (0..m).map(|i| i as f64 / (m - 1) as f64 * std::f64::consts::TAU).collect();
+    let xy: Vec<f64> = u.iter().flat_map(|u| [u.cos(), u.sin()]).collect();
+    let knots = vec![1.0, 2.0, 3.0, 4.5, 5.5];
+
+    let s = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?.least_squares_spline(knots.clone())?;
+    assert_eq!(s.t.len(), knots.len() + 8);
+    assert!(s.report.e_rms < 1E-3);
+
+    let s = ClosedCubicSplineFit2D::new(u.clone(), xy)?.least_squares_spline(knots)?;
+    assert_eq!(s.t.len(), 13);
+    assert_abs_diff_eq!(s.t[4], 1.0);
+    assert_abs_diff_eq!(s.t[0], 4.5 - std::f64::consts::TAU, epsilon = 1E-12);
+    assert!(s.report.e_rms < 1E-3);
+    Ok(())
+}
+
+#[test]
+fn least_squares_clocur_knots() -> Result<()> {
+    // each B-spline has a data point in its support, but there is no subset with a separate point for each of them
+    let u = vec![4.0, 5.0, 16.0, 23.0, 25.0, 29.0, 31.0, 37.0, 39.0, 40.0, 50.0];
+    let xy: Vec<f64> = u.iter().flat_map(|u| {
+        let a = (u - 4.0) / 46.0 * std::f64::consts::TAU;
+        [a.cos(), a.sin()]
+    }).collect();
+    let knots = vec![10.5, 12.5, 13.5, 14.5, 17.5, 19.5, 32.5];
+    let e = ClosedCubicSplineFit2D::new(u.clone(), xy.clone())?.least_squares_spline(knots).unwrap_err();
+    assert_eq!(e, FitError::SchoenbergWhitney { routine: "clocur", index: 5, interval: [12.5, 19.5] });
+
+    let e = ClosedParameterSplineCurveFit::<1, 2>::new(u, xy)?.least_squares_spline(Vec::new()).unwrap_err();
+    assert!(matches!(e, FitError::InvalidInput { routine: "clocur", .. }));
+    Ok(())
+}
@@ -1,11 +1,11 @@
 
-use splinify::{CubicSplineFit, SplineCurveFit, ParameterSplineCurveFit, Result, read_csv_xy, SplineCurveData};
+use splinify::{CubicPeriodicSplineFit, CubicSplineFit, FitReport, SplineCurveFit, ParameterSplineCurveFit, Result, read_csv_xy, SplineCurveData};
 
 #[test]
 fn test_smoothing() -> Result<()> {
     let (x,y) =  read_csv_xy("tests/data/leds4000.csv")? ;
 
-    let d = SplineCurveFit::<3>::new(x.clone(), y.clone());
+    let d = SplineCurveFit::<3>::new(x.clone(), y.clone())?;
     let d = d.smoothing_spline(0.01)?;
     println!("knots {:?}", d.t);
     println!("number of knots: {}", d.t.len());
@@ -24,7 +24,7 @@ fn test_cardinal() -> Result<()> {
 
     let (x,y) =  read_csv_xy("tests/data/leds4000.csv")? ;
 
-    let d = CubicSplineFit::new(x.clone(), y.clone());
+    let d = CubicSplineFit::new(x.clone(), y.clone())?;
     let tc = d.cardinal_spline(10.0)?;
     println!("knots {:?}", tc.t);
     println!("number of knots: {}", tc.t.len());
@@ -35,6 +35,30 @@ fn test_cardinal() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cardinal_knots() -> Result<()> {
+    let x: Vec<f64> = (0..=40).map(|i| i as f64 * 0.5).collect();
+    let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+
+    // bounds at multiples of dt: the knots at the bounds are left out
+    let tc = CubicSplineFit::new(x.clone(), y.clone())?.cardinal_spline(5.0)?;
+    assert_eq!(tc.t[3..tc.t.len() - 3], [0.0, 5.0, 10.0, 15.0, 20.0]);
+
+    // a single interior knot
+    let tc = CubicSplineFit::new(x.clone(), y.clone())?.cardinal_spline(12.0)?;
+    assert_eq!(tc.t[3..tc.t.len() - 3], [0.0, 12.0, 20.0]);
+
+    assert!(CubicSplineFit::new(x.clone(), y.clone())?.cardinal_spline(25.0).is_err());
+
+    // the same knots for parametric and periodic fits
+    let tc = ParameterSplineCurveFit::<3,1>::new(x.clone(), y.clone())?.cardinal_spline(12.0)?;
+    assert_eq!(tc.t[3..tc.t.len() - 3], [0.0, 12.0, 20.0]);
+    let yp: Vec<f64> = x.iter().map(|x| (x * std::f64::consts::PI / 10.0).sin()).collect();
+    let tc = CubicPeriodicSplineFit::new(x, yp)?.cardinal_spline(5.0)?;
+    assert_eq!(tc.t[3..tc.t.len() - 3], [0.0, 5.0, 10.0, 15.0, 20.0]);
+    Ok(())
+}
+
 #[test]
 fn test_cardinal_1d() -> Result<()> {
     use splinify::CubicSplineFit1D;
@@ -98,3 +122,84 @@ fn constrained_cardinal_spline() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_smoothing_session() -> Result<()> {
+    let (x,y) =  read_csv_xy("tests/data/leds4000.csv")? ;
+
+    let mut session = CubicSplineFit::new(x.clone(), y.clone())?.smoothing_session(0.05)?;
+    for rms in [0.02, 0.01, 0.005] {
+        let step = session.smooth_more(rms)?;
+        assert!(step.e_rms <= rms * 1.001);
+    }
+    let steps = session.steps();
+    assert_eq!(steps.len(), 4);
+    assert!(steps.windows(2).all(|w| w[1].t.len() >= w[0].t.len()));
+
+    let s = session.spline();
+    assert_eq!(s.t, steps[3].t);
+    let s2: spliny::SplineCurve<3, 1> = session.into();
+    assert_eq!(s.c, s2.c);
+
+    let d = CubicSplineFit::new(x, y)?.smoothing_spline_optimize(0.05, 0.8, |_, _, _, drms| drms < 1E-4, None)?;
+    println!("number of knots: {}", d.t.len());
+    Ok(())
+}
+
+#[test]
+fn test_fit_report() -> Result<()> {
+    let (x,y) =  read_csv_xy("tests/data/leds4000.csv")? ;
+    let m = x.len();
+
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.with_edf().smoothing_spline(0.01)?;
+    let r = &s.report;
+    assert_eq!(r.ier, 0);
+    assert_eq!(r.knots, s.t.len());
+    assert_eq!(r.residuals.len(), m);
+    assert!(r.e_rms <= 0.01 * 1.001);
+    assert!(r.edf.unwrap() < (s.t.len() - 4) as f64);
+
+    // the smoother matrix trace is only calculated on request; least squares fits count their coefficients
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.smoothing_spline(0.01)?;
+    assert_eq!(s.report.edf, None);
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.cardinal_spline(10.0)?;
+    assert_eq!(s.report.edf, Some((s.t.len() - 4) as f64));
+
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.interpolating_spline()?;
+    assert_eq!(s.report.routine, "curfit");
+    assert_eq!(s.report.ier, -1);
+    assert_eq!(s.report.warning(), Some("interpolating spline (fp=0)"));
+    assert!(s.report.fp < 1E-10);
+
+    let s = CubicSplineFit::new(x, y)?.smoothing_spline_optimize(0.05, 0.8, |_, _, _, drms| drms < 1E-4, None)?;
+    assert!(s.report.iterations >= 3);
+    Ok(())
+}
+
+#[test]
+fn test_report_warnings() {
+    let report = |routine, ier| FitReport {
+        routine, fp: 0.0, e_rms: 0.0, residuals: Vec::new(), knots: 8, ier, edf: None, iterations: 1,
+    };
+    assert!(report("curfit", -2).warning().unwrap().contains("polynomial"));
+    assert!(report("clocur", -2).warning().unwrap().contains("constant"));
+    assert!(report("concon", -1).warning().unwrap().contains("adding knots"));
+    assert!(report("concon", -3).warning().unwrap().contains("nest"));
+    assert_eq!(report("concon", 0).warning(), None);
+    assert_eq!(report("curfit", 0).warning(), None);
+}
+
+#[test]
+fn test_interval() -> Result<()> {
+    let (x,y) =  read_csv_xy("tests/data/leds4000.csv")? ;
+
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.set_interval(380.0, 780.0)?.cardinal_spline(10.0)?;
+    assert_eq!(s.t[0], 380.0);
+    assert_eq!(s.t[s.t.len()-1], 780.0);
+    assert_eq!(s.t[4], 390.0);
+
+    let s = CubicSplineFit::new(x, y)?.set_interval(360.0, 830.0)?.smoothing_spline(0.01)?;
+    assert_eq!(s.t[0], 360.0);
+    assert_eq!(s.t[s.t.len()-1], 830.0);
+    Ok(())
+}
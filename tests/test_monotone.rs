@@ -0,0 +1,34 @@
+mod common;
+
+use splinify::{CubicSplineFit, Monotonicity, Result};
+
+fn is_monotone(v: &[f64], sign: f64) -> bool {
+    v.windows(2).all(|w| sign * (w[1] - w[0]) >= 0.0)
+}
+
+#[test]
+fn monotone_increasing() -> Result<()> {
+    let (x, y) = common::cdf();
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.smoothing_spline(0.005)?;
+    assert!(!is_monotone(&s.c, 1.0));
+
+    let s = CubicSplineFit::new(x.clone(), y.clone())?.monotone_spline(0.005, Monotonicity::Increasing)?;
+    assert!(is_monotone(&s.c, 1.0));
+    assert!(s.report.edf.is_none());
+    assert!(s.report.e_rms < 0.02);
+
+    let xs: Vec<f64> = (0..=1000).map(|i| i as f64 / 100.0).collect();
+    let ys = s.evaluate(&xs)?;
+    assert!(is_monotone(&ys, 1.0));
+    Ok(())
+}
+
+#[test]
+fn monotone_decreasing() -> Result<()> {
+    let (x, y) = common::cdf();
+    let y: Vec<f64> = y.iter().map(|y| 1.0 - y).collect();
+    let s = CubicSplineFit::new(x, y)?.monotone_spline(0.0, Monotonicity::Decreasing)?;
+    assert!(is_monotone(&s.c, -1.0));
+    assert_eq!(s.report.ier, 0); // not the interpolating spline curfit reports for rms=0
+    Ok(())
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{CubicMultiSeriesFit, CubicSplineFit, FitError, Result};
+
+// three spectra, sampled at the same wavelengths
+fn spectra() -> (Vec<f64>, Vec<Vec<f64>>) {
+    let spectra = [450.0, 530.0, 620.0].map(|peak| common::spectrum(2, peak, 15.0, 0.0));
+    let x = spectra[0].0.clone();
+    (x, spectra.into_iter().map(|(_, y)| y).collect())
+}
+
+#[test]
+fn shared_knots() -> Result<()> {
+    let (x, y) = spectra();
+    let fits = CubicMultiSeriesFit::<3>::new(x.clone(), y.clone())?.smoothing_spline(0.001)?;
+    assert_eq!(fits.len(), 3);
+    assert!(fits.iter().all(|f| f.t == fits[0].t && f.c.len() == fits[0].t.len() - 4));
+    for (f, y) in fits.iter().zip(&y) {
+        assert_eq!(f.report.residuals.len(), x.len());
+        assert!(f.report.e_rms < 0.002);
+        let ys = f.evaluate(&x)?;
+        ys.iter().zip(y).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 0.01));
+    }
+
+    // a cardinal fit of a single series gives the same spline as CubicSplineFit
+    let fits = CubicMultiSeriesFit::<3>::new(x.clone(), y.clone())?.cardinal_spline(10.0)?;
+    let s = CubicSplineFit::new(x, y[1].clone())?.cardinal_spline(10.0)?;
+    assert_eq!(fits[1].t.len(), s.t.len());
+    fits[1].c.iter().zip(&s.c).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-8));
+    Ok(())
+}
+
+#[test]
+fn series_sizes() {
+    let (x, mut y) = spectra();
+    assert!(matches!(
+        CubicMultiSeriesFit::<2>::new(x.clone(), y.clone()),
+        Err(FitError::SizeMismatch { what: "series", expected: 2, found: 3, .. })
+    ));
+    y[2].pop();
+    assert!(matches!(CubicMultiSeriesFit::<3>::new(x, y), Err(FitError::SizeMismatch { what: "y", .. })));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{AutoParameterSplineCurveFit, ClosedCubicSplineFit2D, FitError, Result};
+use splinify::dierckx_val::evaluate;
+
+#[test]
+fn chord_length() -> Result<()> {
+    let (_, u) = AutoParameterSplineCurveFit::<1, 2>::new(vec![0.0, 0.0, 3.0, 4.0, 3.0, 9.0])?.interpolating_spline()?;
+    assert_eq!(u, vec![0.0, 0.5, 1.0]);
+
+    let square = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0];
+    assert_eq!(ClosedCubicSplineFit2D::with_chord_length(square)?.u(), [0.0, 0.25, 0.5, 0.75, 1.0]);
+    Ok(())
+}
+
+#[test]
+fn automatic_parameters() -> Result<()> {
+    let xy = common::ellipse(45, 2.0, 1.0, 1.0);
+    let (s, u) = AutoParameterSplineCurveFit::<3, 2>::new(xy.clone())?.smoothing_spline(1E-4)?;
+    assert_eq!(u.len(), xy.len() / 2);
+    assert_abs_diff_eq!(u[0], 0.0);
+    assert_abs_diff_eq!(u[u.len() - 1], 1.0);
+
+    // points on a circle are spaced equally
+    u.iter().enumerate().for_each(|(i, u)| assert_abs_diff_eq!(*u, i as f64 / 45.0, epsilon = 1E-6));
+
+    // the parameter values map the data points onto the curve
+    let xyt = evaluate(&s, &u)?;
+    xyt.iter().zip(xy.iter()).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-3));
+    Ok(())
+}
+
+#[test]
+fn automatic_parameters_input() {
+    assert!(matches!(
+        AutoParameterSplineCurveFit::<1, 2>::new(vec![0.0, 0.0, 1.0, 1.0, 1.0, 1.0]),
+        Err(FitError::NotAscending { routine: "parcur", what: "u", index: 2 })
+    ));
+    assert!(matches!(
+        AutoParameterSplineCurveFit::<3, 2>::new(vec![0.0, 0.0, 1.0, 1.0, 2.0, 0.0]),
+        Err(FitError::TooFewDataPoints { routine: "parcur", found: 3, min: 4 })
+    ));
+    assert!(ClosedCubicSplineFit2D::with_chord_length(vec![0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0]).is_err());
+}
+
+#[test]
+fn even_degree() -> Result<()> {
+    let xy = common::ellipse(45, 2.0, 1.0, 1.0);
+    let (s, u) = AutoParameterSplineCurveFit::<4, 2>::new(xy.clone())?.smoothing_spline(1E-4)?;
+    let xyt = evaluate(&s, &u)?;
+    xyt.iter().zip(xy.iter()).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-3));
+    Ok(())
+}
+
+#[test]
+fn closed_automatic_parameters() -> Result<()> {
+    let xy = common::ellipse(36, 10.0, 2.0, 1.0);
+    let fit = ClosedCubicSplineFit2D::with_chord_length(xy.clone())?;
+    let u = fit.u().to_vec();
+    let s = fit.smoothing_spline(1E-4)?;
+    let xyt = evaluate(&s, &u)?;
+    xyt.iter().zip(xy.iter()).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-3));
+    Ok(())
+}
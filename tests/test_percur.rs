@@ -0,0 +1,46 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{CubicPeriodicSplineFit, PeriodicSplineCurveFit, Result};
+use splinify::dierckx_val::{evaluate, evaluate_derivative};
+use std::f64::consts::TAU;
+
+#[test]
+fn periodic_smoothing_spline() -> Result<()> {
+    let (x, y) = common::periodic_data();
+    let s = CubicPeriodicSplineFit::new(x.clone(), y.clone())?.smoothing_spline(1E-3)?;
+
+    // values and derivatives match at both ends of the period
+    let ends = [0.0, TAU];
+    for order in 0..3 {
+        let d = evaluate_derivative(&s, &ends, order)?;
+        assert_abs_diff_eq!(d[0], d[1], epsilon = 1E-8);
+    }
+
+    let yt = evaluate(&s, &x)?;
+    yt.iter().zip(y.iter()).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-2));
+    Ok(())
+}
+
+#[test]
+fn periodic_cardinal_spline() -> Result<()> {
+    let (x, y) = common::periodic_data();
+    let s = CubicPeriodicSplineFit::new(x.clone(), y.clone())?.cardinal_spline(0.5)?;
+    let d = evaluate_derivative(&s, &[0.0, TAU], 1)?;
+    assert_abs_diff_eq!(d[0], d[1], epsilon = 1E-8);
+
+    let yt = evaluate(&s, &x)?;
+    yt.iter().zip(y.iter()).for_each(|(a, b)| assert_abs_diff_eq!(a, b, epsilon = 1E-2));
+    Ok(())
+}
+
+#[test]
+fn periodic_even_degree() -> Result<()> {
+    let (x, y) = common::periodic_data();
+    let s = PeriodicSplineCurveFit::<4>::new(x.clone(), y.clone())?.smoothing_spline(1E-3)?;
+    for order in 0..4 {
+        let d = evaluate_derivative(&s, &[0.0, TAU], order)?;
+        assert_abs_diff_eq!(d[0], d[1], epsilon = 1E-8);
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{CubicSplineFit, ParameterSplineCurveFit, Result, RobustLoss};
+
+// sine, with small deterministic noise, and spikes at indices 40, 41 and 120
+fn spiked_sine(m: usize) -> (Vec<f64>, Vec<f64>) {
+    let x = common::grid(m - 1, (m - 1) as f64);
+    let mut y = common::noisy_sine(&x, 6.0, 0.02);
+    y[40] += 2.0;
+    y[41] += 1.5;
+    y[120] -= 3.0;
+    (x, y)
+}
+
+#[test]
+fn loss_weights() {
+    let h = RobustLoss::huber();
+    assert_eq!(h.weight(1.0), 1.0);
+    assert_abs_diff_eq!(h.weight(-2.69), 0.5, epsilon = 1E-12);
+    let b = RobustLoss::Bisquare(4.0);
+    assert_eq!(b.weight(0.0), 1.0);
+    assert_abs_diff_eq!(b.weight(2.0), 0.5625, epsilon = 1E-12);
+    assert_eq!(b.weight(5.0), 0.0);
+}
+
+#[test]
+fn robust_curfit() -> Result<()> {
+    let (x, y) = spiked_sine(200);
+    for loss in [RobustLoss::huber(), RobustLoss::bisquare()] {
+        let r = CubicSplineFit::new(x.clone(), y.clone())?.robust_smoothing_spline(0.01, loss, None)?;
+        assert_eq!(r.outliers, vec![40, 41, 120]);
+        let v = splinify::dierckx_val::evaluate(&r.fit, &[x[40], x[120]])?;
+        assert_abs_diff_eq!(v[0], (6.0 * x[40]).sin(), epsilon = 0.05);
+        assert_abs_diff_eq!(v[1], (6.0 * x[120]).sin(), epsilon = 0.05);
+    }
+    Ok(())
+}
+
+#[test]
+fn robust_concur() -> Result<()> {
+    let (u, y) = spiked_sine(200);
+    let xy: Vec<f64> = u.iter().zip(&y).flat_map(|(&u, &y)| [u, y]).collect();
+    let r = ParameterSplineCurveFit::<3, 2>::new(u, xy)?.robust_smoothing_spline(0.01, RobustLoss::bisquare(), None)?;
+    assert_eq!(r.outliers, vec![40, 41, 120]);
+    assert!(r.weights[40] < 1E-3);
+    Ok(())
+}
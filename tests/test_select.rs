@@ -0,0 +1,58 @@
+mod common;
+
+use approx::assert_abs_diff_eq;
+use splinify::{
+    rms_range, ClosedCubicSplineFit2D, CubicSplineFit, Criterion, ParameterSplineCurveFit, Result,
+};
+
+#[test]
+fn select_gcv() -> Result<()> {
+    // sine with a noise rms value of about 0.03
+    let x = common::grid(199, 199.0);
+    let y = common::noisy_sine(&x, 6.0, 0.1);
+    let rms = rms_range(0.2, 0.005, 20);
+    let sel = CubicSplineFit::new(x, y)?.select_smoothing(&rms, Criterion::Gcv)?;
+    assert_eq!(sel.candidates.len(), 20);
+    let best = &sel.candidates[sel.selected];
+    assert!(sel.candidates.iter().all(|c| c.gcv >= best.gcv));
+    // the selected fit leaves residuals of about the noise level
+    assert!(best.e_rms > 0.015 && best.e_rms < 0.05);
+    assert_eq!(sel.spline.t.len(), best.knots);
+    Ok(())
+}
+
+#[test]
+fn select_loo_and_lcurve() -> Result<()> {
+    let x = common::grid(199, 199.0);
+    let y = common::noisy_sine(&x, 6.0, 0.1);
+    let rms = rms_range(0.2, 0.005, 20);
+    let fit = CubicSplineFit::new(x, y)?;
+    let loo = fit.select_smoothing(&rms, Criterion::LeaveOneOut)?;
+    let lc = fit.select_smoothing(&rms, Criterion::LCurve)?;
+    assert_eq!(loo.candidates, lc.candidates);
+    assert!(lc.candidates[lc.selected].curvature.is_some());
+    // effective degrees of freedom increase with decreasing target rms values
+    assert!(loo.candidates[19].edf > loo.candidates[0].edf);
+    Ok(())
+}
+
+#[test]
+fn select_parameter_curves() -> Result<()> {
+    let m = 101;
+    let u: Vec<f64> = (0..m).map(|i| i as f64 / (m - 1) as f64 * std::f64::consts::TAU).collect();
+    let noise = |i: usize| 0.02 * ((i * 7919 % 13) as f64 / 12.0 - 0.5);
+    let xy: Vec<f64> = u.iter().enumerate().flat_map(|(i, u)| [2.0 * u.cos() + noise(i), u.sin() - noise(i + 3)]).collect();
+    let rms = rms_range(0.1, 0.001, 12);
+
+    let sel = ParameterSplineCurveFit::<3, 2>::new(u.clone(), xy.clone())?.select_smoothing(&rms, Criterion::Gcv)?;
+    assert!(sel.candidates[sel.selected].edf < m as f64);
+
+    let mut xy_closed = xy;
+    let n = xy_closed.len();
+    xy_closed[n - 2] = xy_closed[0];
+    xy_closed[n - 1] = xy_closed[1];
+    let sel = ClosedCubicSplineFit2D::new(u, xy_closed)?.select_smoothing(&rms, Criterion::LeaveOneOut)?;
+    let best = &sel.candidates[sel.selected];
+    assert_abs_diff_eq!(best.e_rms, 0.006, epsilon = 0.006);
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use splinify::{FitError, OwnedSplineCurveData, SplineCurveData, SPLINE_DATA_VERSION};
+use spliny::SplineCurve;
+
+fn spline() -> SplineCurve<3, 2> {
+    let t = vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0];
+    let c = vec![0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 1.0, 0.0, 1.0, 0.0];
+    SplineCurve::new(t, c)
+}
+
+#[test]
+fn json_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let s = spline();
+    let json = serde_json::to_string(&SplineCurveData::from(&s))?;
+    assert!(json.starts_with(&format!("{{\"version\":{SPLINE_DATA_VERSION},")));
+
+    let d: OwnedSplineCurveData = serde_json::from_str(&json)?;
+    assert_eq!(d, OwnedSplineCurveData::from(&s));
+    let s2: SplineCurve<3, 2> = d.try_into()?;
+    assert_eq!(s2.t, s.t);
+    assert_eq!(s2.c, s.c);
+    assert_eq!(s2.evaluate(&[0.25, 0.75])?, s.evaluate(&[0.25, 0.75])?);
+
+    // data written without a version field, by earlier releases
+    let d: OwnedSplineCurveData = serde_json::from_str(r#"{"k":3,"n":2,"t":[0,0,0,0,0.5,1,1,1,1],"c":[0,1,2,3,4,0,1,0,1,0]}"#)?;
+    assert_eq!(d.version, 0);
+    let s3: SplineCurve<3, 2> = d.try_into()?;
+    assert_eq!(s3.c, s.c);
+    Ok(())
+}
+
+#[test]
+fn invalid_spline_data() {
+    let d = OwnedSplineCurveData::from(&spline());
+    let e = SplineCurve::<3, 1>::try_from(d.clone()).unwrap_err();
+    assert!(matches!(e, FitError::InvalidSplineData { .. }));
+    assert_eq!(e.routine(), None);
+    assert!(SplineCurve::<5, 2>::try_from(d.clone()).is_err());
+
+    let mut newer = d.clone();
+    newer.version = SPLINE_DATA_VERSION + 1;
+    assert!(matches!(SplineCurve::<3, 2>::try_from(newer), Err(FitError::InvalidSplineData { .. })));
+
+    let mut short = d.clone();
+    short.c.pop();
+    let e = SplineCurve::<3, 2>::try_from(short).unwrap_err();
+    assert_eq!(e.to_string(), "invalid spline data; 9 coefficients, 9 knots of a spline of dimension 2 need 10");
+
+    let mut unordered = d.clone();
+    unordered.t[4] = 1.5;
+    assert!(matches!(SplineCurve::<3, 2>::try_from(unordered), Err(FitError::InvalidSplineData { .. })));
+
+    let mut nan = d;
+    nan.c[3] = f64::NAN;
+    let e = SplineCurve::<3, 2>::try_from(nan).unwrap_err();
+    assert_eq!(e.to_string(), "invalid spline data; coefficient c[3] is not finite");
+}
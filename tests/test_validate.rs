@@ -0,0 +1,76 @@
+use splinify::{
+    ClosedCubicSplineFit2D, CubicSplineFit, FitError, ParameterSplineCurveFit, PeriodicSplineCurveFit,
+    Result,
+};
+
+#[test]
+fn spline_curve_fit_input() -> Result<()> {
+    let x = vec![0.0, 1.0, 2.0, 2.0, 4.0];
+    let y = vec![0.0, 1.0, 4.0, 9.0, 16.0];
+    assert!(matches!(
+        CubicSplineFit::new(x, y.clone()),
+        Err(FitError::NotAscending { routine: "curfit", what: "x", index: 3 })
+    ));
+
+    let x = vec![0.0, 1.0, 2.0, 3.0, 4.0];
+    let y_nan = vec![0.0, 1.0, f64::NAN, 9.0, 16.0];
+    assert!(matches!(CubicSplineFit::new(x.clone(), y_nan), Err(FitError::NonFinite { what: "y", index: 2, .. })));
+    assert!(matches!(
+        CubicSplineFit::new(x[..3].to_vec(), y[..3].to_vec()),
+        Err(FitError::TooFewDataPoints { found: 3, min: 4, .. })
+    ));
+    assert!(matches!(CubicSplineFit::new(x.clone(), y[1..].to_vec()), Err(FitError::SizeMismatch { what: "y", .. })));
+
+    let fit = CubicSplineFit::new(x, y)?;
+    assert!(matches!(
+        fit.clone().set_weights(vec![1.0, 1.0, 0.0, 1.0, 1.0]),
+        Err(FitError::NonPositiveWeight { index: 2, value: 0.0, .. })
+    ));
+    assert!(matches!(fit.clone().set_interval(0.5, 4.0), Err(FitError::InvalidInput { routine: "curfit", .. })));
+    assert!(matches!(fit.clone().set_interval(0.0, 3.5), Err(FitError::InvalidInput { routine: "curfit", .. })));
+    assert!(matches!(fit.set_interval(f64::NEG_INFINITY, 4.0), Err(FitError::InvalidInput { routine: "curfit", .. })));
+    Ok(())
+}
+
+#[test]
+fn parameter_curve_fit_input() -> Result<()> {
+    let u = vec![0.0, 1.0, 0.5, 3.0];
+    let xn = vec![0.0, 0.0, 1.0, 1.0, 2.0, 0.0, 3.0, 1.0];
+    assert!(matches!(
+        ParameterSplineCurveFit::<3, 2>::new(u, xn.clone()),
+        Err(FitError::NotAscending { routine: "concur", what: "u", index: 2 })
+    ));
+
+    let u = vec![0.0, 1.0, 2.0, 3.0];
+    let mut xn_inf = xn.clone();
+    xn_inf[5] = f64::INFINITY;
+    assert!(matches!(
+        ParameterSplineCurveFit::<3, 2>::new(u.clone(), xn_inf),
+        Err(FitError::NonFinite { what: "xn", index: 5, .. })
+    ));
+
+    let fit = ParameterSplineCurveFit::<3, 2>::new(u, xn)?;
+    assert!(matches!(fit.weights(vec![1.0, -1.0, 1.0, 1.0]), Err(FitError::NonPositiveWeight { index: 1, .. })));
+    Ok(())
+}
+
+#[test]
+fn closed_and_periodic_fit_input() {
+    let u = vec![0.0, 1.0, 2.0, f64::NAN, 4.0];
+    let xn = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0];
+    assert!(matches!(ClosedCubicSplineFit2D::new(u, xn), Err(FitError::NonFinite { what: "u", index: 3, .. })));
+
+    let x = vec![0.0, 1.0, 3.0, 2.0, 4.0];
+    let y = vec![0.0, 1.0, 0.0, -1.0, 0.0];
+    let e = PeriodicSplineCurveFit::<3>::new(x, y).err().unwrap();
+    assert_eq!(e, FitError::NotAscending { routine: "clocur", what: "x", index: 3 });
+    assert_eq!(e.to_string(), "clocur: x[3] is not larger than x[2]: values should be strictly ascending");
+
+    let x = vec![0.0, 1.0, 2.0, 3.0, 4.0];
+    let y = vec![0.0, 1.0, 0.0, -1.0, 0.5];
+    let e = PeriodicSplineCurveFit::<3>::new(x.clone(), y).err().unwrap();
+    assert!(matches!(e, FitError::InvalidInput { routine: "clocur", .. }));
+    let y = vec![0.0, 1.0, 0.0, -1.0, 1E-12];
+    assert!(PeriodicSplineCurveFit::<2>::new(x.clone(), y.clone()).is_ok());
+    assert!(matches!(PeriodicSplineCurveFit::<6>::new(x, y), Err(FitError::InvalidDegree { k: 6, .. })));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use splinify::{CubicSplineFit, FitWorkspace, ParameterSplineCurveFit, Result};
+
+// data sets of different sizes, in steps of 0.1
+fn data(m: usize) -> (Vec<f64>, Vec<f64>) {
+    let x = common::grid(m - 1, 10.0);
+    let y = common::noisy_sine(&x, 1.0, 0.03);
+    (x, y)
+}
+
+#[test]
+fn curfit_workspace() -> Result<()> {
+    let workspace = FitWorkspace::new();
+    assert_eq!(workspace.capacity(), 0);
+
+    let (x, y) = data(200);
+    let s = CubicSplineFit::with_workspace(x.clone(), y.clone(), &workspace)?.smoothing_spline(0.01)?;
+    let s0 = CubicSplineFit::new(x, y)?.smoothing_spline(0.01)?;
+    assert_eq!(s.t, s0.t);
+    assert_eq!(s.c, s0.c);
+    let capacity = workspace.capacity();
+    assert!(capacity > 0);
+
+    // smaller data sets reuse the arrays
+    for m in [200, 150, 100] {
+        let (x, y) = data(m);
+        CubicSplineFit::with_workspace(x, y, &workspace)?.smoothing_spline(0.01)?;
+        assert_eq!(workspace.capacity(), capacity);
+    }
+    Ok(())
+}
+
+#[test]
+fn concur_workspace() -> Result<()> {
+    let workspace = FitWorkspace::new();
+    let (u, y) = data(200);
+    let xy: Vec<f64> = u.iter().zip(&y).flat_map(|(&u, &y)| [u, y]).collect();
+    let s = ParameterSplineCurveFit::<3, 2>::with_workspace(u.clone(), xy.clone(), &workspace)?.smoothing_spline(0.01)?;
+    let s0 = ParameterSplineCurveFit::<3, 2>::new(u, xy)?.smoothing_spline(0.01)?;
+    assert_eq!(s.t, s0.t);
+    assert_eq!(s.c, s0.c);
+    assert!(workspace.capacity() > 0);
+    Ok(())
+}
+
+#[test]
+fn workspace_after_error() -> Result<()> {
+    let workspace = FitWorkspace::new();
+    let (x, y) = data(100);
+    let fit = CubicSplineFit::with_workspace(x, y, &workspace)?;
+    assert_eq!(workspace.capacity(), 0);
+
+    // the arrays are returned by a failed fit too
+    assert!(fit.least_squares_spline(vec![5.0, 2.0]).is_err());
+    assert!(workspace.capacity() > 0);
+    Ok(())
+}
+
+#[test]
+fn clones_keep_their_arrays() -> Result<()> {
+    let workspace = FitWorkspace::new();
+    let (x, y) = data(100);
+    let fit = CubicSplineFit::with_workspace(x, y, &workspace)?;
+
+    // a clone, as made by select_smoothing, does not replace the arrays of the fitter in the work space
+    drop(fit.clone());
+    assert_eq!(workspace.capacity(), 0);
+    drop(fit);
+    assert!(workspace.capacity() > 0);
+    Ok(())
+}